@@ -29,7 +29,11 @@ export function activate(context: ExtensionContext) {
   const clientOptions: LanguageClientOptions = {
     documentSelector: [{ scheme: "file", language: "sysy" }],
     synchronize: {
-      fileEvents: workspace.createFileSystemWatcher("**/*.sy")
+      fileEvents: workspace.createFileSystemWatcher("**/*.sy"),
+      configurationSection: "sysyAnalyzer"
+    },
+    initializationOptions: {
+      sylibPath: workspace.getConfiguration("sysyAnalyzer").get<string>("sylibPath")
     },
     outputChannelName: "SySy Language Server",
     revealOutputChannelOn: 4,
@@ -0,0 +1,35 @@
+use tower_lsp::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem, CallHierarchyOutgoingCall,
+    CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+};
+
+use super::Backend;
+
+impl Backend {
+    pub async fn prepare_call_hierarchy_handler(&self, params: CallHierarchyPrepareParams)
+    -> Result<Option<Vec<CallHierarchyItem>>, tower_lsp::jsonrpc::Error> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let doc_handler = self.documents.get(&uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let doc = doc_handler.lock().await;
+        Ok(doc.prepare_call_hierarchy(&uri, params.text_document_position_params.position))
+    }
+
+    pub async fn incoming_calls_handler(&self, params: CallHierarchyIncomingCallsParams)
+    -> Result<Option<Vec<CallHierarchyIncomingCall>>, tower_lsp::jsonrpc::Error> {
+        let uri = &params.item.uri;
+        let doc_handler = self.documents.get(uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let doc = doc_handler.lock().await;
+        Ok(Some(doc.incoming_calls(uri, &params.item)))
+    }
+
+    pub async fn outgoing_calls_handler(&self, params: CallHierarchyOutgoingCallsParams)
+    -> Result<Option<Vec<CallHierarchyOutgoingCall>>, tower_lsp::jsonrpc::Error> {
+        let uri = &params.item.uri;
+        let doc_handler = self.documents.get(uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let doc = doc_handler.lock().await;
+        Ok(Some(doc.outgoing_calls(uri, &params.item)))
+    }
+}
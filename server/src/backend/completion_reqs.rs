@@ -1,10 +1,10 @@
 use super::Backend;
-use tower_lsp::lsp_types::{CompletionItem, CompletionParams, CompletionResponse};
-use super::document_handler::SymbolKind;
+use tower_lsp::lsp_types::{CompletionItem, CompletionParams, CompletionResponse, Documentation};
+use super::document_handler::{runtime_functions, SymbolKind};
 use tree_sitter::Point;
 
-const KEYWORDS: &[&str] = &[
-    "int", "void", "const", "if", "else", "while", 
+pub(super) const KEYWORDS: &[&str] = &[
+    "int", "float", "void", "const", "if", "else", "while", 
     "break", "continue", "return"];
 impl Backend {
     pub async fn completion_handler(&self, params: CompletionParams) -> Result<Option<CompletionResponse>,
@@ -52,8 +52,9 @@ impl Backend {
             if symbol.name.to_lowercase().starts_with(&last_token_text.to_lowercase()) {
                 let kind = match symbol.kind {
                     SymbolKind::Function => tower_lsp::lsp_types::CompletionItemKind::FUNCTION,
-                    SymbolKind::Variable => tower_lsp::lsp_types::CompletionItemKind::VARIABLE,
-                    _ => tower_lsp::lsp_types::CompletionItemKind::TEXT,
+                    SymbolKind::Variable | SymbolKind::Parameter | SymbolKind::Array =>
+                        tower_lsp::lsp_types::CompletionItemKind::VARIABLE,
+                    SymbolKind::Constant => tower_lsp::lsp_types::CompletionItemKind::CONSTANT,
                 };
                 completions.push(CompletionItem {
                     label: symbol.name,
@@ -62,6 +63,20 @@ impl Backend {
                 });
             };
         }
+        for func in runtime_functions().iter() {
+            // Declarations in the document shadow the runtime library
+            if func.name.to_lowercase().starts_with(&last_token_text.to_lowercase())
+                && !completions.iter().any(|c| c.label == func.name)
+            {
+                completions.push(CompletionItem {
+                    label: func.name.clone(),
+                    kind: Some(tower_lsp::lsp_types::CompletionItemKind::FUNCTION),
+                    detail: Some(func.declaration()),
+                    documentation: func.doc.clone().map(Documentation::String),
+                    ..Default::default()
+                });
+            }
+        }
         Ok(
             Some(CompletionResponse::Array(
                 completions
@@ -1,5 +1,7 @@
-use tower_lsp::lsp_types::{Hover, HoverParams, HoverContents, Position, GotoDefinitionParams, GotoDefinitionResponse};
-use tree_sitter::Point;
+use tower_lsp::lsp_types::{
+    Hover, HoverParams, HoverContents, LanguageString, MarkedString, Position, GotoDefinitionParams,
+    GotoDefinitionResponse,
+};
 
 use super::Backend;
 impl Backend {
@@ -11,19 +13,20 @@ impl Backend {
             line: params.text_document_position_params.position.line,
             character: params.text_document_position_params.position.character,
         };
-        let definition = doc.find_definition(pos).ok_or_else(|| {
+        if let Some(func) = doc.runtime_function_at(pos) {
+            let mut contents = vec![MarkedString::LanguageString(LanguageString {
+                language: "sysy".to_string(),
+                value: func.declaration(),
+            })];
+            contents.extend(func.doc.map(MarkedString::String));
+            return Ok(Some(Hover {
+                contents: HoverContents::Array(contents),
+                range: None,
+            }));
+        }
+        let mut definition_text = doc.definition_text(pos).ok_or_else(|| {
             tower_lsp::jsonrpc::Error::invalid_params("Definition not found at the given position")
         })?;
-        let mut definition_text = doc.get_text_range(
-            Point {
-                row: definition.start.line as usize,
-                column: definition.start.character as usize,
-            },
-            Point {
-                row: definition.end.line as usize,
-                column: definition.end.character as usize,
-            },
-        );
 
         if definition_text.len() > 200
         {
@@ -34,8 +37,8 @@ impl Backend {
         Ok(
             Some(Hover {
                 contents: HoverContents::Scalar(
-                    tower_lsp::lsp_types::MarkedString::LanguageString(
-                        tower_lsp::lsp_types::LanguageString {
+                    MarkedString::LanguageString(
+                        LanguageString {
                             language: "sysy".to_string(), // Using 'c' language for SysY (similar to C)
                             value: definition_text,
                         }
@@ -62,19 +65,12 @@ impl Backend {
             tower_lsp::jsonrpc::Error::invalid_params("Definition not found at the given position")
         })?;
 
-        let location = tower_lsp::lsp_types::Location {
-            uri: params.text_document_position_params.text_document.uri,
-            range: tower_lsp::lsp_types::Range {
-                start: Position {
-                    line: definition.start.line as u32,
-                    character: definition.start.character as u32,
-                },
-                end: Position {
-                    line: definition.end.line as u32,
-                    character: definition.end.character as u32,
-                },
-            },
+        let link = tower_lsp::lsp_types::LocationLink {
+            origin_selection_range: Some(definition.origin_selection_range),
+            target_uri: params.text_document_position_params.text_document.uri,
+            target_range: definition.target_range,
+            target_selection_range: definition.target_selection_range,
         };
-        Ok(Some(GotoDefinitionResponse::Scalar(location)))
+        Ok(Some(GotoDefinitionResponse::Link(vec![link])))
     }               
 }
\ No newline at end of file
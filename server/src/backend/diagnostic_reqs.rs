@@ -0,0 +1,22 @@
+use tower_lsp::lsp_types::Url;
+
+use super::Backend;
+
+impl Backend {
+    /// Re-run the checks on a document and publish the results to the client
+    pub async fn update_diagnostics(&self, uri: Url, version: Option<i32>) {
+        let diagnostics = {
+            let Some(doc_handler) = self.documents.get(&uri) else {
+                return;
+            };
+            let doc = doc_handler.lock().await;
+            doc.query_diagnostics(&uri)
+        };
+        self.client.publish_diagnostics(uri, diagnostics, version).await;
+    }
+
+    /// Remove every diagnostic previously published for a document
+    pub async fn clear_diagnostics(&self, uri: Url) {
+        self.client.publish_diagnostics(uri, Vec::new(), None).await;
+    }
+}
@@ -0,0 +1,194 @@
+//! Structured views over the syntax tree.
+//!
+//! Statements and expressions have a node of their own with a field for
+//! every part. The views here leave out the wrapper nodes between them,
+//! such as `Exp` and `PrimaryExp`.
+
+use tree_sitter::Node;
+
+/// A statement or declaration inside a `Block`
+#[derive(Debug, Clone)]
+pub enum Stmt<'a> {
+    Decl(Node<'a>),
+    Block(Node<'a>),
+    Assign {
+        target: Node<'a>,
+        value: Option<Node<'a>>,
+    },
+    Exp(Option<Node<'a>>),
+    If {
+        condition: Option<Node<'a>>,
+        consequence: Option<Box<Stmt<'a>>>,
+        alternative: Option<Box<Stmt<'a>>>,
+    },
+    While {
+        condition: Option<Node<'a>>,
+        body: Option<Box<Stmt<'a>>>,
+    },
+    Break(Node<'a>),
+    Continue(Node<'a>),
+    Return {
+        keyword: Node<'a>,
+        value: Option<Node<'a>>,
+    },
+}
+
+/// The statements and declarations of a `Block`, in order
+pub fn block_items(block: Node) -> Vec<Stmt> {
+    let mut cursor = block.walk();
+    let items = block.named_children(&mut cursor).filter_map(stmt_view).collect();
+    items
+}
+
+/// View a statement, `Block` or declaration as a [`Stmt`]
+pub fn stmt_view(node: Node) -> Option<Stmt> {
+    let field = |name: &str| node.child_by_field_name(name);
+    let nested = |name: &str| field(name).and_then(stmt_view).map(Box::new);
+    match node.kind() {
+        "VarDecl" | "ConstDecl" => Some(Stmt::Decl(node)),
+        "Block" => Some(Stmt::Block(node)),
+        "AssignStmt" => Some(Stmt::Assign {
+            target: field("target")?,
+            value: field("value"),
+        }),
+        "ExpStmt" => Some(Stmt::Exp(field("value"))),
+        "IfStmt" => Some(Stmt::If {
+            condition: field("condition"),
+            consequence: nested("consequence"),
+            alternative: nested("alternative"),
+        }),
+        "WhileStmt" => Some(Stmt::While {
+            condition: field("condition"),
+            body: nested("body"),
+        }),
+        "BreakStmt" => Some(Stmt::Break(node.child(0)?)),
+        "ContinueStmt" => Some(Stmt::Continue(node.child(0)?)),
+        "ReturnStmt" => Some(Stmt::Return {
+            keyword: node.child(0)?,
+            value: field("value"),
+        }),
+        _ => None,
+    }
+}
+
+/// An expression with parentheses and single-child wrappers removed
+#[derive(Debug, Clone, Copy)]
+pub enum Exp<'a> {
+    Number(Node<'a>),
+    Lval(Node<'a>),
+    Call(Node<'a>),
+    Unary {
+        operator: Node<'a>,
+        operand: Node<'a>,
+    },
+    Binary {
+        left: Node<'a>,
+        operator: Node<'a>,
+        right: Node<'a>,
+    },
+}
+
+/// View an `Exp`, `ConstExp`, `PrimaryExp` or one of their children as an [`Exp`]
+pub fn exp_view(node: Node) -> Option<Exp> {
+    match node.kind() {
+        "Number" => Some(Exp::Number(node)),
+        "Lval" => Some(Exp::Lval(node)),
+        "FuncCall" => Some(Exp::Call(node)),
+        "UnaryExp" => Some(Exp::Unary {
+            operator: node.child_by_field_name("operator")?,
+            operand: node.child_by_field_name("operand")?,
+        }),
+        "BinaryExp" => Some(Exp::Binary {
+            left: node.child_by_field_name("left")?,
+            operator: node.child_by_field_name("operator")?,
+            right: node.child_by_field_name("right")?,
+        }),
+        // A `PrimaryExp` is either a leaf or a parenthesized expression
+        "Exp" | "ConstExp" | "PrimaryExp" => exp_view(node.named_child(0)?),
+        _ => None,
+    }
+}
+
+/// The identifier of an `Lval` or the callee of a `FuncCall`
+pub fn ident_of(node: Node) -> Option<Node> {
+    match node.kind() {
+        "Lval" => node.child_by_field_name("ident"),
+        "FuncCall" => node.child_by_field_name("callee"),
+        _ => None,
+    }
+}
+
+/// Whether an `Lval` is the target of an assignment statement, the only
+/// place where one appears outside of an expression
+pub fn is_assign_target(lval: Node) -> bool {
+    lval.parent().is_some_and(|p| p.kind() == "AssignStmt")
+}
+
+/// The subscript expressions of an `Lval`
+pub fn lval_indices(lval: Node) -> Vec<Node> {
+    let mut cursor = lval.walk();
+    let indices = lval.children_by_field_name("index", &mut cursor).collect();
+    indices
+}
+
+/// The argument expressions of a `FuncCall`
+pub fn call_args(call: Node) -> Vec<Node> {
+    let mut cursor = call.walk();
+    let Some(params) = call.named_children(&mut cursor).find(|c| c.kind() == "FuncRParams") else {
+        return Vec::new();
+    };
+    let args = params.named_children(&mut cursor).filter(|c| c.kind() == "Exp").collect();
+    args
+}
+
+/// The dimension expressions of a `VarArrayQualifier`, `ConstArrayQualifier`
+/// or `FuncArraryQualifier`
+pub fn array_dims(qualifier: Node) -> Vec<Node> {
+    let mut cursor = qualifier.walk();
+    let dims = qualifier.named_children(&mut cursor)
+        .filter(|c| c.kind() == "Exp" || c.kind() == "ConstExp")
+        .collect();
+    dims
+}
+
+/// The body `Block` of a `FuncDef`
+pub fn func_body(func: Node) -> Option<Node> {
+    let mut cursor = func.walk();
+    let body = func.named_children(&mut cursor).find(|c| c.kind() == "Block");
+    body
+}
+
+/// The initializer of a `VarDef` or `ConstDef`
+pub fn def_init(def: Node) -> Option<Node> {
+    let mut cursor = def.walk();
+    let init = def.named_children(&mut cursor)
+        .find(|c| c.kind() == "InitVal" || c.kind() == "ConstInitVal");
+    init
+}
+
+/// Parse the text of an integer literal in decimal, octal or hexadecimal
+pub fn parse_int_literal(text: &str) -> Option<i64> {
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()
+    } else if text.len() > 1 && text.starts_with('0') {
+        i64::from_str_radix(&text[1..], 8).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+/// The value of a `Float` literal, rounded to the single precision of SysY.
+/// Hexadecimal literals such as `0x1.8p3` scale their digits by a power of two.
+pub fn parse_float_literal(text: &str) -> Option<f32> {
+    let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) else {
+        return text.parse().ok();
+    };
+    let (mantissa, exponent) = hex.split_once(['p', 'P'])?;
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let mut value = 0f64;
+    for digit in int_part.chars().chain(frac_part.chars()) {
+        value = value * 16.0 + digit.to_digit(16)? as f64;
+    }
+    let exponent: i32 = exponent.parse().ok()?;
+    Some((value * 2f64.powi(exponent - 4 * frac_part.len() as i32)) as f32)
+}
@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tree_sitter::Node;
+
+use super::ast::{self, Exp};
+use super::types::BaseType;
+use super::{init_list, DocHandler};
+
+/// Bound on how many constant definitions an evaluation may follow, which
+/// also stops self-referential definitions such as `const int a = a;`
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstError<'a> {
+    /// The node refers to something that is not a compile-time constant
+    NotConstant(Node<'a>),
+    /// The node divides an int by zero
+    DivisionByZero(Node<'a>),
+    /// The value cannot be determined, for example because of syntax errors
+    Unknown,
+}
+
+/// The value of a constant expression
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstValue {
+    Int(i32),
+    Float(f32),
+}
+
+impl ConstValue {
+    pub fn as_int(self) -> Option<i32> {
+        match self {
+            ConstValue::Int(v) => Some(v),
+            ConstValue::Float(_) => None,
+        }
+    }
+
+    fn as_float(self) -> f32 {
+        match self {
+            ConstValue::Int(v) => v as f32,
+            ConstValue::Float(v) => v,
+        }
+    }
+
+    /// Whether the value counts as true in a condition
+    pub fn is_true(self) -> bool {
+        match self {
+            ConstValue::Int(v) => v != 0,
+            ConstValue::Float(v) => v != 0.0,
+        }
+    }
+
+    /// The value converted to `base` as by an assignment, which truncates
+    /// floats toward zero
+    pub fn convert(self, base: BaseType) -> ConstValue {
+        match (self, base) {
+            (ConstValue::Float(v), BaseType::Int) => ConstValue::Int(v as i32),
+            (ConstValue::Int(v), BaseType::Float) => ConstValue::Float(v as f32),
+            _ => self,
+        }
+    }
+}
+
+/// A node remembered by its id and span, which unlike a `Node` does not
+/// borrow the syntax tree
+#[derive(Debug, Clone, Copy)]
+struct NodeKey {
+    id: usize,
+    start: usize,
+    end: usize,
+}
+
+impl NodeKey {
+    fn of(node: Node) -> Self {
+        NodeKey { id: node.id(), start: node.start_byte(), end: node.end_byte() }
+    }
+}
+
+/// A `ConstError` that can be kept across evaluations
+#[derive(Debug, Clone, Copy)]
+enum CachedError {
+    NotConstant(NodeKey),
+    DivisionByZero(NodeKey),
+}
+
+/// The type, dimensions and initializer layout of a constant definition
+#[derive(Debug)]
+struct ConstLayout {
+    base: BaseType,
+    dims: Vec<usize>,
+    /// The expression initializing each flat index
+    leaves: HashMap<usize, NodeKey>,
+}
+
+/// Results of constant evaluation over the current syntax tree. Every
+/// constant is evaluated once, so chains of constants built from earlier ones
+/// take linear rather than exponential time.
+#[derive(Debug, Default)]
+pub struct ConstCache {
+    /// The layout of each constant by `ConstDef` id
+    layouts: HashMap<usize, Arc<ConstLayout>>,
+    /// The value of each element of a constant by `ConstDef` id and flat index
+    values: HashMap<(usize, usize), Result<ConstValue, CachedError>>,
+}
+
+impl DocHandler {
+    /// Evaluate a constant expression with the 32-bit wrapping arithmetic of
+    /// SysY, converting `int` operands to `float` when mixed with one
+    pub(super) fn eval_const<'a>(&'a self, exp: Node<'a>) -> Result<ConstValue, ConstError<'a>> {
+        self.eval_const_at(exp, 0)
+    }
+
+    fn eval_const_at<'a>(&'a self, exp: Node<'a>, depth: usize) -> Result<ConstValue, ConstError<'a>> {
+        match ast::exp_view(exp).ok_or(ConstError::Unknown)? {
+            Exp::Number(number) => {
+                let text = self.node_text(number);
+                let value = if number.named_child(0).is_some_and(|n| n.kind() == "Float") {
+                    ast::parse_float_literal(&text).map(ConstValue::Float)
+                } else {
+                    ast::parse_int_literal(&text).map(|v| ConstValue::Int(v as i32))
+                };
+                value.ok_or(ConstError::Unknown)
+            }
+            Exp::Lval(lval) => self.eval_const_lval(lval, depth),
+            Exp::Call(call) => Err(ConstError::NotConstant(call)),
+            Exp::Unary { operator, operand } => {
+                let v = self.eval_const_at(operand, depth)?;
+                match (operator.kind(), v) {
+                    ("+", _) => Ok(v),
+                    ("-", ConstValue::Int(v)) => Ok(ConstValue::Int(v.wrapping_neg())),
+                    ("-", ConstValue::Float(v)) => Ok(ConstValue::Float(-v)),
+                    ("!", _) => Ok(ConstValue::Int(!v.is_true() as i32)),
+                    _ => Err(ConstError::Unknown),
+                }
+            }
+            Exp::Binary { left, operator, right } => {
+                let l = self.eval_const_at(left, depth)?;
+                // Logical operators short-circuit like they do at runtime
+                match operator.kind() {
+                    "&&" if !l.is_true() => return Ok(ConstValue::Int(0)),
+                    "||" if l.is_true() => return Ok(ConstValue::Int(1)),
+                    "&&" | "||" => {
+                        let r = self.eval_const_at(right, depth)?;
+                        return Ok(ConstValue::Int(r.is_true() as i32));
+                    }
+                    _ => {}
+                }
+                let r = self.eval_const_at(right, depth)?;
+                match (l, r) {
+                    (ConstValue::Int(l), ConstValue::Int(r)) => Self::eval_int_binary(exp, operator, l, r),
+                    _ => Self::eval_float_binary(operator, l.as_float(), r.as_float()),
+                }
+            }
+        }
+    }
+
+    fn eval_int_binary<'a>(exp: Node<'a>, operator: Node<'a>, l: i32, r: i32) -> Result<ConstValue, ConstError<'a>> {
+        let value = match operator.kind() {
+            "+" => l.wrapping_add(r),
+            "-" => l.wrapping_sub(r),
+            "*" => l.wrapping_mul(r),
+            "/" | "%" if r == 0 => return Err(ConstError::DivisionByZero(exp)),
+            "/" => l.wrapping_div(r),
+            "%" => l.wrapping_rem(r),
+            "<" => (l < r) as i32,
+            ">" => (l > r) as i32,
+            "<=" => (l <= r) as i32,
+            ">=" => (l >= r) as i32,
+            "==" => (l == r) as i32,
+            "!=" => (l != r) as i32,
+            _ => return Err(ConstError::Unknown),
+        };
+        Ok(ConstValue::Int(value))
+    }
+
+    fn eval_float_binary<'a>(operator: Node<'a>, l: f32, r: f32) -> Result<ConstValue, ConstError<'a>> {
+        let value = match operator.kind() {
+            "+" => l + r,
+            "-" => l - r,
+            "*" => l * r,
+            // Float division by zero gives an infinity or NaN as at runtime
+            "/" => l / r,
+            "<" => return Ok(ConstValue::Int((l < r) as i32)),
+            ">" => return Ok(ConstValue::Int((l > r) as i32)),
+            "<=" => return Ok(ConstValue::Int((l <= r) as i32)),
+            ">=" => return Ok(ConstValue::Int((l >= r) as i32)),
+            "==" => return Ok(ConstValue::Int((l == r) as i32)),
+            "!=" => return Ok(ConstValue::Int((l != r) as i32)),
+            // `%` takes no float operands, which the type check reports
+            _ => return Err(ConstError::Unknown),
+        };
+        Ok(ConstValue::Float(value))
+    }
+
+    fn eval_const_lval<'a>(&'a self, lval: Node<'a>, depth: usize) -> Result<ConstValue, ConstError<'a>> {
+        if depth >= MAX_DEPTH {
+            return Err(ConstError::Unknown);
+        }
+        let ident = ast::ident_of(lval).ok_or(ConstError::Unknown)?;
+        let decl = self.resolve_ident(ident).ok_or(ConstError::Unknown)?;
+        if decl.kind() != "ConstDecl" {
+            return Err(ConstError::NotConstant(lval));
+        }
+        let def = self.declarator(decl, &self.node_text(ident)).ok_or(ConstError::Unknown)?;
+        let layout = self.const_layout(decl, def, depth)?;
+        let indices = ast::lval_indices(lval);
+        if indices.len() != layout.dims.len() {
+            return Err(ConstError::Unknown);
+        }
+        let mut flat_index = 0;
+        for (index, dim) in indices.into_iter().zip(&layout.dims) {
+            let value = self.eval_const_at(index, depth)?.as_int().ok_or(ConstError::Unknown)?;
+            if value < 0 || value as usize >= *dim {
+                return Err(ConstError::Unknown);
+            }
+            flat_index = flat_index * dim + value as usize;
+        }
+        self.const_element(def, &layout, flat_index, depth)
+    }
+
+    /// The value at `flat_index` of the constant `def`, evaluated at most once
+    fn const_element<'a>(&'a self, def: Node<'a>, layout: &ConstLayout, flat_index: usize, depth: usize)
+    -> Result<ConstValue, ConstError<'a>>
+    {
+        let key = (def.id(), flat_index);
+        let cached = self.const_cache.lock().unwrap().values.get(&key).copied();
+        if let Some(cached) = cached {
+            return cached.map_err(|e| match e {
+                CachedError::NotConstant(n) => self.node_of_key(n).map_or(ConstError::Unknown, ConstError::NotConstant),
+                CachedError::DivisionByZero(n) => self.node_of_key(n).map_or(ConstError::Unknown, ConstError::DivisionByZero),
+            });
+        }
+        let result = match layout.leaves.get(&flat_index) {
+            Some(leaf) => {
+                let leaf = self.node_of_key(*leaf).ok_or(ConstError::Unknown)?;
+                self.eval_const_at(leaf, depth + 1).map(|v| v.convert(layout.base))
+            }
+            // Elements without an initializer are zero
+            None => Ok(ConstValue::Int(0).convert(layout.base)),
+        };
+        // Running out of depth depends on where the evaluation started, so
+        // unknown values are evaluated again next time
+        let cached = match result {
+            Ok(value) => Some(Ok(value)),
+            Err(ConstError::NotConstant(n)) => Some(Err(CachedError::NotConstant(NodeKey::of(n)))),
+            Err(ConstError::DivisionByZero(n)) => Some(Err(CachedError::DivisionByZero(NodeKey::of(n)))),
+            Err(ConstError::Unknown) => None,
+        };
+        if let Some(cached) = cached {
+            self.const_cache.lock().unwrap().values.insert(key, cached);
+        }
+        result
+    }
+
+    /// The layout of the constant `def` declared by `decl`, which is built
+    /// once and kept while the syntax tree is unchanged
+    fn const_layout<'a>(&'a self, decl: Node<'a>, def: Node<'a>, depth: usize) -> Result<Arc<ConstLayout>, ConstError<'a>> {
+        if let Some(layout) = self.const_cache.lock().unwrap().layouts.get(&def.id()) {
+            return Ok(layout.clone());
+        }
+        let base = decl.child_by_field_name("type")
+            .and_then(|t| self.base_type(t))
+            .ok_or(ConstError::Unknown)?;
+        let dims = self.const_dims(def, depth)?;
+        if init_list::array_size(&dims).is_none() {
+            return Err(ConstError::Unknown);
+        }
+        let init = ast::def_init(def).ok_or(ConstError::Unknown)?;
+        let leaves = init_list::layout(init, &dims).leaves.into_iter()
+            .map(|(index, leaf)| (index, NodeKey::of(leaf)))
+            .collect();
+        let layout = Arc::new(ConstLayout { base, dims, leaves });
+        self.const_cache.lock().unwrap().layouts.insert(def.id(), layout.clone());
+        Ok(layout)
+    }
+
+    /// The dimensions of a constant array, which must all be positive
+    fn const_dims<'a>(&'a self, def: Node<'a>, depth: usize) -> Result<Vec<usize>, ConstError<'a>> {
+        let Some(qualifier) = def.child_by_field_name("array_qualifier") else {
+            return Ok(Vec::new());
+        };
+        ast::array_dims(qualifier).into_iter()
+            .map(|dim| match self.eval_const_at(dim, depth + 1) {
+                Ok(ConstValue::Int(size)) if size > 0 => Ok(size as usize),
+                _ => Err(ConstError::Unknown),
+            })
+            .collect()
+    }
+
+    /// Find a node remembered by `NodeKey::of` in the current syntax tree
+    fn node_of_key(&self, key: NodeKey) -> Option<Node<'_>> {
+        let mut node = self.syntax_tree.root_node().descendant_for_byte_range(key.start, key.end)?;
+        // Nodes spanning the same bytes are ancestors of the smallest one
+        while node.id() != key.id {
+            node = node.parent().filter(|p| p.start_byte() == key.start && p.end_byte() == key.end)?;
+        }
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::DocHandler;
+    use super::ConstError;
+    use super::ConstValue::{Float, Int};
+
+    /// Evaluate the initializer of the last global in `text`
+    fn eval(text: &str) -> Result<super::ConstValue, String> {
+        let doc = DocHandler::from_text(text);
+        let comp_unit = doc.syntax_tree.root_node().named_child(0).unwrap();
+        let decl = comp_unit.named_child(comp_unit.named_child_count() - 1).unwrap();
+        let def = decl.child_by_field_name("defs").unwrap();
+        let init = super::ast::def_init(def).unwrap();
+        doc.eval_const(init.named_child(0).unwrap()).map_err(|e| match e {
+            ConstError::NotConstant(n) => format!("not constant: {}", doc.node_text(n)),
+            ConstError::DivisionByZero(n) => format!("division by zero: {}", doc.node_text(n)),
+            ConstError::Unknown => "unknown".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_eval_const() {
+        assert_eq!(eval("const int a = 1 + 2 * 3 - 010 / 0x2;"), Ok(Int(3)));
+        assert_eq!(eval("const int a = -7 % 3 + (2 < 3) + !5 + (1 == 1 && 0 || 2);"), Ok(Int(1)));
+        assert_eq!(eval("const int a = 2147483647 + 1;"), Ok(Int(i32::MIN)));
+        assert_eq!(eval("const int a = -2 * 3 + 10 / (1 + 1) < 0 == 0 || 0 && 1;"), Ok(Int(0)));
+        assert_eq!(eval("const int n = 4; const int m = n * n;"), Ok(Int(16)));
+        assert_eq!(eval("const int a[2][3] = {1, 2, 3, {4, 5}}; const int b = a[1][1] + a[1][2];"), Ok(Int(5)));
+        assert_eq!(eval("const int a = 0 && 1 / 0;"), Ok(Int(0)));
+        assert_eq!(eval("const int a = 1 / (2 - 2);"), Err("division by zero: 1 / (2 - 2)".to_string()));
+        assert_eq!(eval("int n = 4; const int m = n + 1;"), Err("not constant: n".to_string()));
+        assert_eq!(eval("const int a = getint();"), Err("not constant: getint()".to_string()));
+    }
+
+    #[test]
+    fn test_eval_const_chain() {
+        // Each constant uses the previous one three times, so evaluating the
+        // chain without the cache would take 3^40 steps
+        let mut text = "const int c0 = 1;".to_string();
+        for k in 1..=40 {
+            text.push_str(&format!(" const int c{k} = c{0} + c{0} - c{0};", k - 1));
+        }
+        assert_eq!(eval(&text), Ok(Int(1)));
+        text.push_str(" const int a[2] = {c40, c40 + a[0]}; const int b = a[1] * a[1] + a[0];");
+        assert_eq!(eval(&text), Ok(Int(5)));
+    }
+
+    #[test]
+    fn test_eval_float_const() {
+        assert_eq!(eval("const float a = 1.5e-3 * 2;"), Ok(Float(1.5e-3 * 2.0)));
+        assert_eq!(eval("const float a = 0x1.8p3 + 0X.8P-1 + 1.;"), Ok(Float(13.25)));
+        assert_eq!(eval("const float a = 7 / 2 + 7 / 2.0 - .5;"), Ok(Float(6.0)));
+        assert_eq!(eval("const float a = 1.5; const int b = a * 3;"), Ok(Float(4.5)));
+        assert_eq!(eval("const int a = -2.7; const float b = a;"), Ok(Int(-2)));
+        assert_eq!(eval("const float a[2] = {1}; const int b = (a[0] > 0.5) + !0.0 + (1.5 && 1);"), Ok(Int(3)));
+        assert_eq!(eval("const float a = 1.0 / 0;"), Ok(Float(f32::INFINITY)));
+        assert_eq!(eval("const float a = 1 / 0 * 1.0;"), Err("division by zero: 1 / 0".to_string()));
+    }
+}
@@ -0,0 +1,102 @@
+use tower_lsp::lsp_types::Diagnostic;
+use tree_sitter::Node;
+
+use super::super::ast;
+use super::super::types::{SymbolType, ValueType};
+use super::super::{DocHandler, node_range, walk_tree};
+use super::error;
+
+/// Whether an argument of type `arg` can be passed for a parameter of type `param`.
+/// Scalars convert between `int` and `float`, while arrays must match in
+/// their element type. The first dimension of an array parameter is never checked.
+fn is_compatible(arg: &ValueType, param: &ValueType) -> bool {
+    if arg.is_arithmetic() && param.is_arithmetic() {
+        return true;
+    }
+    if arg.base != param.base || arg.dims.len() != param.dims.len() {
+        return false;
+    }
+    arg.dims.iter().zip(&param.dims).skip(1).all(|(a, p)| match (a, p) {
+        (Some(a), Some(p)) => a == p,
+        _ => true,
+    })
+}
+
+impl DocHandler {
+    /// Check the number and shape of the arguments of every `FuncCall`
+    pub(super) fn check_calls(&self, diagnostics: &mut Vec<Diagnostic>) {
+        walk_tree(self.syntax_tree.root_node(), |n| {
+            if n.kind() == "FuncCall" {
+                self.check_call(n, diagnostics);
+            }
+        });
+    }
+
+    fn check_call(&self, call: Node, diagnostics: &mut Vec<Diagnostic>) {
+        let Some(ident) = ast::ident_of(call) else {
+            return;
+        };
+        let Some(SymbolType::Function { params, variadic, .. }) = self.ident_type(ident) else {
+            return;
+        };
+        let name = self.node_text(ident);
+        let args = ast::call_args(call);
+        if args.len() < params.len() || (args.len() > params.len() && !variadic) {
+            let quantity = if args.len() < params.len() { "few" } else { "many" };
+            diagnostics.push(error(node_range(call), format!(
+                "too {} arguments to function '{}': expected {}, found {}",
+                quantity, name, params.len(), args.len())));
+        }
+        for (idx, (arg, param)) in args.iter().zip(&params).enumerate() {
+            // Errors inside the argument itself are reported by the type checker
+            let Some(arg_type) = self.exp_type(*arg, &mut Vec::new()) else {
+                continue;
+            };
+            if !is_compatible(&arg_type, param) {
+                diagnostics.push(error(node_range(*arg), format!(
+                    "argument {} of '{}' expects '{}' but '{}' has type '{}'",
+                    idx + 1, name, param, self.node_text(*arg), arg_type)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Url;
+
+    use super::super::super::DocHandler;
+
+    fn diagnose(text: &str) -> Vec<String> {
+        let doc = DocHandler::from_text(text);
+        let mut diagnostics = Vec::new();
+        doc.check_calls(&mut diagnostics);
+        diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_calls() {
+        let prelude = "int g[2][3]; int f(int n, int a[][4]) { return n; } ";
+        assert!(diagnose("int g[2][4]; int f(int n, int a[][4]) { return n; } \
+            int main() { putarray(f(1, g), g[1]); return 0; }").is_empty());
+        assert_eq!(diagnose(&format!("{}int main() {{ return f(1); }}", prelude)),
+            vec!["too few arguments to function 'f': expected 2, found 1"]);
+        assert_eq!(diagnose(&format!("{}int main() {{ return f(1, g); }}", prelude)),
+            vec!["argument 2 of 'f' expects 'int[][4]' but 'g' has type 'int[2][3]'"]);
+        assert_eq!(diagnose("int main() { putint(1, 2); return 0; }"),
+            vec!["too many arguments to function 'putint': expected 1, found 2"]);
+        assert_eq!(diagnose("int g[2]; int main() { putfloat(1); putint(getfloat()); putfarray(2, g); return 0; }"),
+            vec!["argument 2 of 'putfarray' expects 'float[]' but 'g' has type 'int[2]'"]);
+    }
+
+    #[test]
+    fn test_function_argument() {
+        // The type check reports the argument, so the call check stays quiet
+        let text = "int main() { putch(getarray); putint(main); return 0; }";
+        assert!(diagnose(text).is_empty());
+        let doc = DocHandler::from_text(text);
+        let messages: Vec<_> = doc.query_diagnostics(&Url::parse("file:///test.sy").unwrap())
+            .into_iter().map(|d| d.message).collect();
+        assert_eq!(messages, vec!["'getarray' is a function, not a variable", "'main' is a function, not a variable"]);
+    }
+}
@@ -0,0 +1,150 @@
+use tower_lsp::lsp_types::Diagnostic;
+use tree_sitter::Node;
+
+use super::super::const_eval::{ConstError, ConstValue};
+use super::super::{ast, DocHandler, node_range, walk_tree};
+use super::{error, warning};
+
+/// The expressions at the leaves of an `InitVal` or `ConstInitVal`
+fn init_leaves<'a>(init: Node<'a>, leaves: &mut Vec<Node<'a>>) {
+    let mut cursor = init.walk();
+    for child in init.named_children(&mut cursor) {
+        match child.kind() {
+            "Exp" | "ConstExp" => leaves.push(child),
+            "InitVal" | "ConstInitVal" => init_leaves(child, leaves),
+            _ => {}
+        }
+    }
+}
+
+/// Whether `n` is evaluated at compile time: inside an array dimension, a
+/// constant initializer or a global initializer
+fn in_constant_context(n: Node) -> bool {
+    let mut ancestor = n.parent();
+    while let Some(p) = ancestor {
+        match p.kind() {
+            "VarArrayQualifier" | "ConstArrayQualifier" | "FuncArraryQualifier" | "ConstDef" => return true,
+            "VarDef" => return p.parent()
+                .and_then(|decl| decl.parent())
+                .is_some_and(|scope| scope.kind() == "CompUnit"),
+            _ => ancestor = p.parent(),
+        }
+    }
+    false
+}
+
+impl DocHandler {
+    /// Check that array dimensions, constant initializers and global
+    /// initializers are valid constant expressions, and warn about division
+    /// and remainder by a divisor that folds to zero
+    pub(super) fn check_constants(&self, diagnostics: &mut Vec<Diagnostic>) {
+        walk_tree(self.syntax_tree.root_node(), |n| match n.kind() {
+            "VarArrayQualifier" | "ConstArrayQualifier" | "FuncArraryQualifier" => {
+                for dim in ast::array_dims(n) {
+                    match self.eval_const(dim) {
+                        Ok(ConstValue::Int(size)) if size <= 0 => diagnostics.push(error(node_range(dim),
+                            format!("array dimension must be positive, found {}", size))),
+                        // Float dimensions are reported by the type check
+                        Ok(_) => {}
+                        Err(e) => self.report_const_error(e, "array dimension", diagnostics),
+                    }
+                }
+            }
+            "ConstDef" | "VarDef" => {
+                let is_global = n.parent()
+                    .and_then(|decl| decl.parent())
+                    .is_some_and(|scope| scope.kind() == "CompUnit");
+                if n.kind() == "VarDef" && !is_global {
+                    return;
+                }
+                let (Some(ident), Some(init)) = (n.child_by_field_name("ident"), ast::def_init(n)) else {
+                    return;
+                };
+                let what = if n.kind() == "ConstDef" { "constant" } else { "global variable" };
+                let context = format!("initializer of {} '{}'", what, self.node_text(ident));
+                let mut leaves = Vec::new();
+                init_leaves(init, &mut leaves);
+                for leaf in leaves {
+                    if let Err(e) = self.eval_const(leaf) {
+                        self.report_const_error(e, &context, diagnostics);
+                    }
+                }
+            }
+            "BinaryExp" => self.check_division(n, diagnostics),
+            _ => {}
+        });
+    }
+
+    fn check_division(&self, exp: Node, diagnostics: &mut Vec<Diagnostic>) {
+        let (Some(operator), Some(right)) = (exp.child_by_field_name("operator"), exp.child_by_field_name("right")) else {
+            return;
+        };
+        if !matches!(operator.kind(), "/" | "%") {
+            return;
+        }
+        let is_zero = match self.eval_const(right) {
+            Ok(ConstValue::Int(value)) => value == 0,
+            Ok(ConstValue::Float(value)) => value == 0.0,
+            Err(_) => false,
+        };
+        // Int division by zero in a constant expression is an error reported
+        // with the expression, while float division is well defined there
+        if is_zero && !(in_constant_context(exp) && self.eval_const(exp).is_err()) {
+            diagnostics.push(warning(node_range(exp), "division by zero".to_string()));
+        }
+    }
+
+    fn report_const_error(&self, e: ConstError, context: &str, diagnostics: &mut Vec<Diagnostic>) {
+        match e {
+            ConstError::NotConstant(n) => diagnostics.push(error(node_range(n), format!(
+                "{} must be a constant expression, but '{}' is not constant", context, self.node_text(n)))),
+            ConstError::DivisionByZero(n) => diagnostics.push(error(node_range(n),
+                "division by zero in constant expression".to_string())),
+            ConstError::Unknown => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Url;
+
+    use super::super::super::DocHandler;
+
+    fn diagnose(text: &str) -> Vec<String> {
+        let doc = DocHandler::from_text(text);
+        let mut diagnostics = Vec::new();
+        doc.check_constants(&mut diagnostics);
+        diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_constant_contexts() {
+        assert!(diagnose("const int n = 2; int a[n * 2] = {n}; int main() { int b[n][1]; return 0; }").is_empty());
+        assert_eq!(diagnose("int g; int a[g];"),
+            vec!["array dimension must be a constant expression, but 'g' is not constant"]);
+        assert_eq!(diagnose("int a[2 - 2];"), vec!["array dimension must be positive, found 0"]);
+        assert_eq!(diagnose("const int c = 1 % (1 - 1);"), vec!["division by zero in constant expression"]);
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        assert_eq!(diagnose("int main() { int x = 1 / 0; return x % (2 - 2); }"),
+            vec!["division by zero", "division by zero"]);
+        assert_eq!(diagnose("int main() { float f = 1.0 / 0.0; return f; }"), vec!["division by zero"]);
+        assert!(diagnose("int main() { int x = 0; return 1 / x + x / 2; }").is_empty());
+        assert_eq!(diagnose("const float f = 1.0 / 0;"), vec!["division by zero"]);
+        assert_eq!(diagnose("const float f = 1 / 0 * 1.0;"), vec!["division by zero in constant expression"]);
+    }
+
+    #[test]
+    fn test_constant_chain() {
+        let mut text = "const int c0 = 1;".to_string();
+        for k in 1..=40 {
+            text.push_str(&format!("\nconst int c{k} = c{0} + c{0} - c{0};", k - 1));
+        }
+        text.push_str("\nint a[c40][c40 + 1] = {c40};\nint main() { while (c40) { return a[0][c40]; } }");
+        let doc = DocHandler::from_text(&text);
+        assert!(doc.query_diagnostics(&Url::parse("file:///test.sy").unwrap()).is_empty());
+    }
+}
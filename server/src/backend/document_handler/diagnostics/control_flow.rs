@@ -0,0 +1,118 @@
+use tower_lsp::lsp_types::Diagnostic;
+
+use super::super::ast::{self, Stmt};
+use super::super::types::BaseType;
+use super::super::{DocHandler, node_range};
+use super::{error, warning, FuncContext};
+
+/// Whether a `break` leaves the loop directly enclosing `stmt`
+fn breaks_out(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Break(_) => true,
+        Stmt::Block(block) => ast::block_items(*block).iter().any(breaks_out),
+        Stmt::If { consequence, alternative, .. } => {
+            [consequence, alternative].into_iter().flatten().any(|s| breaks_out(s))
+        }
+        // A nested loop catches its own breaks
+        _ => false,
+    }
+}
+
+impl DocHandler {
+    /// Check `break`/`continue` placement and `return` statements against
+    /// the enclosing function
+    pub(super) fn check_control_flow(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let Some(comp_unit) = self.syntax_tree.root_node().named_child(0) else {
+            return;
+        };
+        let mut cursor = comp_unit.walk();
+        for func in comp_unit.named_children(&mut cursor).filter(|n| n.kind() == "FuncDef") {
+            let Some(block) = ast::func_body(func) else {
+                continue;
+            };
+            let context = self.func_context(func);
+            let body = Stmt::Block(block);
+            self.check_flow_stmt(&body, &context, 0, diagnostics);
+            let non_void = matches!(context.ret, Some(BaseType::Int | BaseType::Float));
+            if non_void && !self.always_returns(&body) {
+                let end = block.child(block.child_count().saturating_sub(1)).unwrap_or(block);
+                diagnostics.push(warning(node_range(end), format!(
+                    "non-void function '{}' does not return a value on every path", context.name)));
+            }
+        }
+    }
+
+    fn check_flow_stmt(&self, stmt: &Stmt, context: &FuncContext, loop_depth: usize, diagnostics: &mut Vec<Diagnostic>) {
+        match stmt {
+            Stmt::Block(block) => {
+                for item in ast::block_items(*block) {
+                    self.check_flow_stmt(&item, context, loop_depth, diagnostics);
+                }
+            }
+            Stmt::If { consequence, alternative, .. } => {
+                for branch in [consequence, alternative].into_iter().flatten() {
+                    self.check_flow_stmt(branch, context, loop_depth, diagnostics);
+                }
+            }
+            Stmt::While { body: Some(body), .. } => {
+                self.check_flow_stmt(body, context, loop_depth + 1, diagnostics);
+            }
+            Stmt::Break(keyword) | Stmt::Continue(keyword) if loop_depth == 0 => {
+                diagnostics.push(error(node_range(*keyword), format!(
+                    "'{}' statement not within a loop", keyword.kind())));
+            }
+            Stmt::Return { keyword, value } => match (context.ret, value) {
+                (Some(BaseType::Void), Some(value)) => {
+                    diagnostics.push(error(node_range(*value), format!(
+                        "void function '{}' should not return a value", context.name)));
+                }
+                (Some(BaseType::Int | BaseType::Float), None) => {
+                    diagnostics.push(error(node_range(*keyword), format!(
+                        "non-void function '{}' should return a value", context.name)));
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// Whether control can never reach the end of `stmt`
+    fn always_returns(&self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Return { .. } => true,
+            Stmt::Block(block) => ast::block_items(*block).iter().any(|s| self.always_returns(s)),
+            Stmt::If { consequence: Some(consequence), alternative: Some(alternative), .. } => {
+                self.always_returns(consequence) && self.always_returns(alternative)
+            }
+            Stmt::While { condition: Some(condition), body, .. } => {
+                // An infinite loop only ends through `return` or `break`
+                self.eval_const(*condition).is_ok_and(|v| v.is_true()) && !body.as_deref().is_some_and(breaks_out)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::DocHandler;
+
+    fn diagnose(text: &str) -> Vec<String> {
+        let doc = DocHandler::from_text(text);
+        let mut diagnostics = Vec::new();
+        doc.check_control_flow(&mut diagnostics);
+        diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_control_flow() {
+        assert!(diagnose("int f(int a) { while (a) { if (a) break; else continue; } if (a) return 1; else { return 2; } } \
+            int g() { while (1) { return 0; } } void h() { return; }").is_empty());
+        assert_eq!(diagnose("int f(int a) { if (a) return 1; }"),
+            vec!["non-void function 'f' does not return a value on every path"]);
+        assert_eq!(diagnose("int f() { while (1) { break; } return 0; } void g() { break; }"),
+            vec!["'break' statement not within a loop"]);
+        assert_eq!(diagnose("void f() { return 1; } int g() { return; }"),
+            vec!["void function 'f' should not return a value", "non-void function 'g' should return a value"]);
+    }
+}
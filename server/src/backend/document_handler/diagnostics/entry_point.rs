@@ -0,0 +1,78 @@
+use tower_lsp::lsp_types::{Diagnostic, Range};
+
+use super::super::types::BaseType;
+use super::super::{DocHandler, node_range};
+use super::error;
+
+impl DocHandler {
+    /// Check that the program defines `int main()`. Only the first definition
+    /// of `main` is checked, as later ones are reported by the redefinition
+    /// check like any other function.
+    pub(super) fn check_entry_point(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let main = self.top_level_functions().into_iter()
+            .find(|f| f.child_by_field_name("ident").is_some_and(|i| self.node_text(i) == "main"));
+        let Some(main) = main else {
+            // Point at the end of the file, where `main` would be added
+            let end = node_range(self.syntax_tree.root_node()).end;
+            diagnostics.push(error(Range { start: end, end },
+                "program does not define the entry point 'int main()'".to_string()));
+            return;
+        };
+        if let Some(ty) = main.child_by_field_name("type") {
+            if self.base_type(ty) != Some(BaseType::Int) {
+                diagnostics.push(error(node_range(ty), "'main' must return 'int'".to_string()));
+            }
+        }
+        let mut cursor = main.walk();
+        let params: Vec<_> = main.children_by_field_name("params", &mut cursor).collect();
+        if let (Some(first), Some(last)) = (params.first(), params.last()) {
+            let range = Range {
+                start: node_range(*first).start,
+                end: node_range(*last).end,
+            };
+            diagnostics.push(error(range, "'main' must not take parameters".to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{Position, Url};
+
+    use super::super::super::DocHandler;
+
+    fn diagnose(text: &str) -> Vec<String> {
+        let doc = DocHandler::from_text(text);
+        let mut diagnostics = Vec::new();
+        doc.check_entry_point(&mut diagnostics);
+        diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_entry_point() {
+        assert!(diagnose("int main() { return 0; }").is_empty());
+        assert_eq!(diagnose("int f() { return 0; }"), vec!["program does not define the entry point 'int main()'"]);
+        assert_eq!(diagnose("void main(int a, int b) { }"),
+            vec!["'main' must return 'int'", "'main' must not take parameters"]);
+    }
+
+    #[test]
+    fn test_missing_entry_point() {
+        let doc = DocHandler::from_text("int f() {\n    return 0;\n}\n");
+        let mut diagnostics = Vec::new();
+        doc.check_entry_point(&mut diagnostics);
+        let end = Position { line: 3, character: 0 };
+        assert_eq!((diagnostics[0].range.start, diagnostics[0].range.end), (end, end));
+    }
+
+    #[test]
+    fn test_duplicate_entry_point() {
+        assert!(diagnose("int main() { return 0; } void main() { }").is_empty());
+        let doc = DocHandler::from_text("int main() { return 0; }\nvoid main() { }");
+        let messages: Vec<_> = doc.query_diagnostics(&Url::parse("file:///test.sy").unwrap())
+            .into_iter()
+            .map(|d| (d.message, d.range.start.line))
+            .collect();
+        assert_eq!(messages, vec![("redefinition of 'main'".to_string(), 1)]);
+    }
+}
@@ -0,0 +1,61 @@
+use tower_lsp::lsp_types::Diagnostic;
+
+use super::super::init_list::{self, InitError};
+use super::super::{ast, DocHandler, node_range, walk_tree};
+use super::error;
+
+impl DocHandler {
+    /// Check the brace structure of initializers against the declared dimensions
+    pub(super) fn check_initializers(&self, diagnostics: &mut Vec<Diagnostic>) {
+        walk_tree(self.syntax_tree.root_node(), |n| {
+            if n.kind() != "VarDef" && n.kind() != "ConstDef" {
+                return;
+            }
+            let (Some(ident), Some(init), Some(ty)) = (n.child_by_field_name("ident"), ast::def_init(n), self.def_type(n)) else {
+                return;
+            };
+            // Unknown or invalid dimensions are reported by the constant check
+            let Some(dims) = ty.dims.iter()
+                .map(|dim| dim.filter(|&size| size > 0).map(|size| size as usize))
+                .collect::<Option<Vec<_>>>() else {
+                return;
+            };
+            let name = self.node_text(ident);
+            for (elem, e) in init_list::layout(init, &dims).errors {
+                let message = match e {
+                    InitError::ExcessElements => format!("excess elements in initializer of '{}' of type '{}'", name, ty),
+                    InitError::BracesAroundScalar => "too many braces around scalar initializer".to_string(),
+                    InitError::Misaligned => format!("nested initializer list is not aligned to a sub-array of '{}'", name),
+                    InitError::ScalarForArray => format!("array '{}' must be initialized with a brace-enclosed list", name),
+                };
+                diagnostics.push(error(node_range(elem), message));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::DocHandler;
+
+    fn diagnose(text: &str) -> Vec<String> {
+        let doc = DocHandler::from_text(text);
+        let mut diagnostics = Vec::new();
+        doc.check_initializers(&mut diagnostics);
+        diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_initializers() {
+        assert!(diagnose("int a[4][2] = {1, 2, {3}, {5, 6}, 7, 8}; int b[2][3][4] = {1, 2, 3, 4, {5}, {}, {{1}, 2}}; \
+            const int c = 1; int d[2] = {};").is_empty());
+        assert_eq!(diagnose("int a[2][2] = {1, 2, 3, 4, 5};"),
+            vec!["excess elements in initializer of 'a' of type 'int[2][2]'"]);
+        assert_eq!(diagnose("int a[2] = {{1}, 2}; int b = {1};"),
+            vec!["too many braces around scalar initializer", "too many braces around scalar initializer"]);
+        assert_eq!(diagnose("int a[2][2] = {1, {2}};"),
+            vec!["nested initializer list is not aligned to a sub-array of 'a'"]);
+        assert_eq!(diagnose("int a[2] = 1;"),
+            vec!["array 'a' must be initialized with a brace-enclosed list"]);
+    }
+}
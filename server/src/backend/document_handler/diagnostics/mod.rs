@@ -0,0 +1,64 @@
+mod syntax;
+mod undeclared;
+mod redefinition;
+mod type_check;
+mod call_check;
+mod control_flow;
+mod const_check;
+mod init_check;
+mod entry_point;
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Range, Url};
+use tree_sitter::Node;
+
+use super::types::BaseType;
+use super::DocHandler;
+
+const DIAGNOSTIC_SOURCE: &str = "sysy-analyzer";
+
+/// The function whose body is being checked
+struct FuncContext {
+    name: String,
+    ret: Option<BaseType>,
+}
+
+fn error(range: Range, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+fn warning(range: Range, message: String) -> Diagnostic {
+    Diagnostic {
+        severity: Some(DiagnosticSeverity::WARNING),
+        ..error(range, message)
+    }
+}
+
+impl DocHandler {
+    fn func_context(&self, func: Node) -> FuncContext {
+        FuncContext {
+            name: func.child_by_field_name("ident").map(|ident| self.node_text(ident)).unwrap_or_default(),
+            ret: func.child_by_field_name("type").and_then(|t| self.base_type(t)),
+        }
+    }
+
+    /// Run every check over the current syntax tree and collect the results
+    pub fn query_diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        self.check_syntax(&mut diagnostics);
+        self.check_undeclared(&mut diagnostics);
+        self.check_redefinition(uri, &mut diagnostics);
+        self.check_types(&mut diagnostics);
+        self.check_calls(&mut diagnostics);
+        self.check_control_flow(&mut diagnostics);
+        self.check_constants(&mut diagnostics);
+        self.check_initializers(&mut diagnostics);
+        self.check_entry_point(&mut diagnostics);
+        diagnostics
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticRelatedInformation, Location, Url};
+use tree_sitter::Node;
+
+use super::super::query_symbols::scope_idents;
+use super::super::{DocHandler, node_range, walk_tree};
+use super::error;
+
+impl DocHandler {
+    /// Report names declared more than once in the same scope
+    pub(super) fn check_redefinition(&self, uri: &Url, diagnostics: &mut Vec<Diagnostic>) {
+        walk_tree(self.syntax_tree.root_node(), |n| {
+            let is_scope = match n.kind() {
+                "CompUnit" | "FuncDef" => true,
+                "Block" => n.parent().is_some_and(|p| p.kind() != "FuncDef"),
+                _ => false,
+            };
+            if is_scope {
+                self.check_scope(uri, scope_idents(n), diagnostics);
+            }
+        });
+    }
+
+    fn check_scope(&self, uri: &Url, idents: Vec<Node>, diagnostics: &mut Vec<Diagnostic>) {
+        let mut seen: HashMap<String, Node> = HashMap::new();
+        for ident in idents {
+            let name = self.node_text(ident);
+            let Some(first) = seen.get(&name) else {
+                seen.insert(name, ident);
+                continue;
+            };
+            let mut diagnostic = error(node_range(ident), format!("redefinition of '{}'", name));
+            diagnostic.related_information = Some(vec![DiagnosticRelatedInformation {
+                location: Location {
+                    uri: uri.clone(),
+                    range: node_range(*first),
+                },
+                message: format!("previous definition of '{}' is here", name),
+            }]);
+            diagnostics.push(diagnostic);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{Diagnostic, Position, Range, Url};
+
+    use super::super::super::DocHandler;
+
+    fn check(text: &str) -> Vec<Diagnostic> {
+        let doc = DocHandler::from_text(text);
+        let mut diagnostics = Vec::new();
+        doc.check_redefinition(&Url::parse("file:///test.sy").unwrap(), &mut diagnostics);
+        diagnostics
+    }
+
+    fn diagnose(text: &str) -> Vec<String> {
+        check(text).into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_redefinition() {
+        assert_eq!(diagnose("int main() { int a; int b, a; return 0; }"), vec!["redefinition of 'a'"]);
+        assert_eq!(diagnose("int f(int x, int x[]) { return 0; }"), vec!["redefinition of 'x'"]);
+        assert_eq!(diagnose("int f(int x) { int x = 1; return x; }"), vec!["redefinition of 'x'"]);
+        assert!(diagnose("int x; int f(int x) { { int x; { const int x = 1; } } while (x) { int x; } return x; }").is_empty());
+        assert_eq!(diagnose("int f() { return 0; } void f() {}"), vec!["redefinition of 'f'"]);
+    }
+
+    #[test]
+    fn test_previous_definition() {
+        let diagnostics = check("int main() {\n    int x;\n    int x;\n    return 0;\n}");
+        assert_eq!(diagnostics.len(), 1);
+        let range = |line, start, end| Range {
+            start: Position { line, character: start },
+            end: Position { line, character: end },
+        };
+        assert_eq!(diagnostics[0].range, range(2, 8, 9));
+        let related = diagnostics[0].related_information.as_ref().unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].location.uri.as_str(), "file:///test.sy");
+        assert_eq!(related[0].location.range, range(1, 8, 9));
+        assert_eq!(related[0].message, "previous definition of 'x' is here");
+    }
+}
@@ -0,0 +1,89 @@
+use tower_lsp::lsp_types::Diagnostic;
+use tree_sitter::Node;
+
+use super::super::{DocHandler, node_range};
+use super::error;
+
+/// Human readable name of a node kind the parser expected but did not find
+fn describe_kind(node: Node) -> String {
+    if !node.is_named() {
+        return format!("'{}'", node.kind());
+    }
+    match node.kind() {
+        "Ident" => "identifier".to_string(),
+        "Exp" | "ConstExp" => "expression".to_string(),
+        "Type" => "type".to_string(),
+        "Block" => "block".to_string(),
+        kind => kind.to_string(),
+    }
+}
+
+fn first_leaf(mut node: Node) -> Node {
+    while let Some(child) = node.child(0) {
+        node = child;
+    }
+    node
+}
+
+impl DocHandler {
+    /// Report `ERROR` and `MISSING` nodes produced by the parser
+    pub(super) fn check_syntax(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let root = self.syntax_tree.root_node();
+        if !root.has_error() {
+            return;
+        }
+        let mut stack = vec![root];
+        while let Some(n) = stack.pop() {
+            if n.is_missing() {
+                diagnostics.push(error(node_range(n), format!("expected {}", describe_kind(n))));
+            } else if n.is_error() {
+                // Nested errors are covered by the outermost one
+                let token = self.node_text(first_leaf(n));
+                diagnostics.push(error(node_range(n), format!("unexpected token '{}'", token)));
+            } else if n.has_error() {
+                let mut cursor = n.walk();
+                let children: Vec<_> = n.children(&mut cursor).collect();
+                stack.extend(children.into_iter().rev());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::DocHandler;
+
+    /// Sample programs that the grammar must parse without errors
+    const SAMPLES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tree-sitter-sysy-parser/test/samples");
+
+    fn diagnose(text: &str) -> Vec<String> {
+        let doc = DocHandler::from_text(text);
+        let mut diagnostics = Vec::new();
+        doc.check_syntax(&mut diagnostics);
+        diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        assert!(diagnose("int main() { return 0; }").is_empty());
+        assert_eq!(diagnose("int main() { int a = 1 return a; }"), vec!["expected ';'"]);
+        assert_eq!(diagnose("int main() { a = 1; } }"), vec!["unexpected token '}'"]);
+        assert!(diagnose("const int a = 1, b[2] = {a, 2};").is_empty());
+    }
+
+    #[test]
+    fn test_samples_parse() {
+        let mut parsed = 0;
+        for entry in std::fs::read_dir(SAMPLES_DIR).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "sy") {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path).unwrap();
+            let doc = DocHandler::from_text(&text);
+            assert!(!doc.syntax_tree.root_node().has_error(), "{}: {:?}", path.display(), diagnose(&text));
+            parsed += 1;
+        }
+        assert!(parsed > 0, "no samples in {}", SAMPLES_DIR);
+    }
+}
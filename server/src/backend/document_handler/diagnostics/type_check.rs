@@ -0,0 +1,294 @@
+use tower_lsp::lsp_types::Diagnostic;
+use tree_sitter::Node;
+
+use super::super::ast::{self, Exp, Stmt};
+use super::super::types::{BaseType, SymbolType, ValueType};
+use super::super::{DocHandler, node_range};
+use super::{error, FuncContext};
+
+/// How a value of `base` is described in messages. Values that must be
+/// arithmetic but not of a particular type are described as ints.
+fn expected_value(base: BaseType) -> &'static str {
+    match base {
+        BaseType::Float => "a float",
+        BaseType::Int | BaseType::Void => "an int",
+    }
+}
+
+impl DocHandler {
+    /// Check that every expression and statement is well typed
+    pub(super) fn check_types(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let Some(comp_unit) = self.syntax_tree.root_node().named_child(0) else {
+            return;
+        };
+        let mut cursor = comp_unit.walk();
+        for item in comp_unit.named_children(&mut cursor) {
+            match item.kind() {
+                "VarDecl" | "ConstDecl" => self.check_decl_types(item, diagnostics),
+                "FuncDef" => self.check_func_types(item, diagnostics),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_func_types(&self, func: Node, diagnostics: &mut Vec<Diagnostic>) {
+        let context = self.func_context(func);
+        let mut cursor = func.walk();
+        for param in func.children_by_field_name("params", &mut cursor) {
+            let (Some(ty), Some(ident)) = (param.child_by_field_name("type"), param.child_by_field_name("ident")) else {
+                continue;
+            };
+            if self.base_type(ty) == Some(BaseType::Void) {
+                diagnostics.push(error(node_range(ident),
+                    format!("parameter '{}' declared void", self.node_text(ident))));
+            }
+        }
+        if let Some(body) = ast::func_body(func) {
+            self.check_block_types(body, &context, diagnostics);
+        }
+    }
+
+    fn check_block_types(&self, block: Node, context: &FuncContext, diagnostics: &mut Vec<Diagnostic>) {
+        for stmt in ast::block_items(block) {
+            self.check_stmt_types(&stmt, context, diagnostics);
+        }
+    }
+
+    fn check_stmt_types(&self, stmt: &Stmt, context: &FuncContext, diagnostics: &mut Vec<Diagnostic>) {
+        match stmt {
+            Stmt::Decl(decl) => self.check_decl_types(*decl, diagnostics),
+            Stmt::Block(block) => self.check_block_types(*block, context, diagnostics),
+            Stmt::Assign { target, value } => {
+                let target_type = self.lval_type(*target, diagnostics);
+                if let Some(ty) = &target_type {
+                    if ty.is_array() {
+                        diagnostics.push(error(node_range(*target), format!(
+                            "cannot assign to array '{}' of type '{}'", self.node_text(*target), ty)));
+                    } else if let Some(SymbolType::Variable { constant: true, .. }) =
+                        ast::ident_of(*target).and_then(|i| self.ident_type(i)) {
+                        diagnostics.push(error(node_range(*target), format!(
+                            "cannot assign to constant '{}'", self.node_text(*target))));
+                    }
+                }
+                if let Some(value) = value {
+                    let expected = target_type.map_or(BaseType::Int, |ty| ty.base);
+                    self.require_value(*value, expected, diagnostics);
+                }
+            }
+            Stmt::Exp(exp) => {
+                if let Some(exp) = exp {
+                    self.exp_type(*exp, diagnostics);
+                }
+            }
+            Stmt::If { condition, consequence, alternative, .. } => {
+                if let Some(condition) = condition {
+                    self.require_value(*condition, BaseType::Int, diagnostics);
+                }
+                for branch in [consequence, alternative].into_iter().flatten() {
+                    self.check_stmt_types(branch, context, diagnostics);
+                }
+            }
+            Stmt::While { condition, body, .. } => {
+                if let Some(condition) = condition {
+                    self.require_value(*condition, BaseType::Int, diagnostics);
+                }
+                if let Some(body) = body {
+                    self.check_stmt_types(body, context, diagnostics);
+                }
+            }
+            Stmt::Return { value: Some(value), .. } => {
+                // Returning a value from a void function is reported by the control flow check
+                match context.ret {
+                    Some(BaseType::Void) => {
+                        self.exp_type(*value, diagnostics);
+                    }
+                    ret => {
+                        self.require_value(*value, ret.unwrap_or(BaseType::Int), diagnostics);
+                    }
+                }
+            }
+            Stmt::Return { value: None, .. } | Stmt::Break(_) | Stmt::Continue(_) => {}
+        }
+    }
+
+    fn check_decl_types(&self, decl: Node, diagnostics: &mut Vec<Diagnostic>) {
+        let base = decl.child_by_field_name("type").and_then(|t| self.base_type(t));
+        let is_void = base == Some(BaseType::Void);
+        let mut cursor = decl.walk();
+        for def in decl.children_by_field_name("defs", &mut cursor) {
+            if let (true, Some(ident)) = (is_void, def.child_by_field_name("ident")) {
+                diagnostics.push(error(node_range(ident),
+                    format!("variable '{}' declared void", self.node_text(ident))));
+            }
+            if let Some(qualifier) = def.child_by_field_name("array_qualifier") {
+                for dim in ast::array_dims(qualifier) {
+                    self.require_int(dim, diagnostics);
+                }
+            }
+            if let Some(init) = ast::def_init(def) {
+                self.check_init_types(init, base.unwrap_or(BaseType::Int), diagnostics);
+            }
+        }
+    }
+
+    fn check_init_types(&self, init: Node, base: BaseType, diagnostics: &mut Vec<Diagnostic>) {
+        let mut cursor = init.walk();
+        for child in init.named_children(&mut cursor) {
+            match child.kind() {
+                "Exp" | "ConstExp" => {
+                    self.require_value(child, base, diagnostics);
+                }
+                "InitVal" | "ConstInitVal" => self.check_init_types(child, base, diagnostics),
+                _ => {}
+            }
+        }
+    }
+
+    /// Check that an expression is a scalar `int`, as needed for array
+    /// dimensions, subscripts and the operands of `%`
+    fn require_int(&self, exp: Node, diagnostics: &mut Vec<Diagnostic>) {
+        if self.require_value(exp, BaseType::Int, diagnostics) == Some(BaseType::Float) {
+            diagnostics.push(error(node_range(exp), format!(
+                "'{}' has type 'float' but an int is expected", self.node_text(exp))));
+        }
+    }
+
+    /// Check that an expression is an `int` or `float` scalar, either of which
+    /// converts implicitly to the `expected` type. Returns the type of the value.
+    fn require_value(&self, exp: Node, expected: BaseType, diagnostics: &mut Vec<Diagnostic>) -> Option<BaseType> {
+        let ty = self.exp_type(exp, diagnostics)?;
+        if ty.base == BaseType::Void {
+            let callee = match ast::exp_view(exp) {
+                Some(Exp::Call(call)) => ast::ident_of(call).map(|i| self.node_text(i)),
+                _ => None,
+            };
+            let message = match callee {
+                Some(callee) => format!("call to void function '{}' cannot be used as a value", callee),
+                None => "void value cannot be used as a value".to_string(),
+            };
+            diagnostics.push(error(node_range(exp), message));
+            None
+        } else if ty.is_array() {
+            diagnostics.push(error(node_range(exp), format!(
+                "'{}' has array type '{}' but {} is expected", self.node_text(exp), ty, expected_value(expected))));
+            None
+        } else {
+            Some(ty.base)
+        }
+    }
+
+    /// Compute the type of an expression, reporting errors found inside it.
+    /// Returns `None` when the type cannot be determined.
+    pub(super) fn exp_type(&self, exp: Node, diagnostics: &mut Vec<Diagnostic>) -> Option<ValueType> {
+        match ast::exp_view(exp)? {
+            Exp::Number(number) => match number.named_child(0) {
+                Some(literal) if literal.kind() == "Float" => Some(ValueType::scalar(BaseType::Float)),
+                _ => Some(ValueType::scalar(BaseType::Int)),
+            },
+            Exp::Lval(lval) => self.lval_type(lval, diagnostics),
+            Exp::Call(call) => {
+                for arg in ast::call_args(call) {
+                    self.exp_type(arg, diagnostics);
+                }
+                let ident = ast::ident_of(call)?;
+                match self.ident_type(ident)? {
+                    SymbolType::Function { ret, .. } => Some(ValueType::scalar(ret)),
+                    SymbolType::Variable { .. } => {
+                        diagnostics.push(error(node_range(ident), format!(
+                            "called object '{}' is not a function", self.node_text(ident))));
+                        None
+                    }
+                }
+            }
+            Exp::Unary { operator, operand } => {
+                let base = self.require_value(operand, BaseType::Int, diagnostics);
+                match operator.kind() {
+                    "!" => Some(ValueType::scalar(BaseType::Int)),
+                    _ => Some(ValueType::scalar(base.unwrap_or(BaseType::Int))),
+                }
+            }
+            Exp::Binary { left, operator, right } => {
+                if operator.kind() == "%" {
+                    self.require_int(left, diagnostics);
+                    self.require_int(right, diagnostics);
+                    return Some(ValueType::scalar(BaseType::Int));
+                }
+                let l = self.require_value(left, BaseType::Int, diagnostics);
+                let r = self.require_value(right, BaseType::Int, diagnostics);
+                // Arithmetic on an int and a float converts the int
+                let is_float = l == Some(BaseType::Float) || r == Some(BaseType::Float);
+                match operator.kind() {
+                    "+" | "-" | "*" | "/" if is_float => Some(ValueType::scalar(BaseType::Float)),
+                    _ => Some(ValueType::scalar(BaseType::Int)),
+                }
+            }
+        }
+    }
+
+    fn lval_type(&self, lval: Node, diagnostics: &mut Vec<Diagnostic>) -> Option<ValueType> {
+        let indices = ast::lval_indices(lval);
+        for index in &indices {
+            self.require_int(*index, diagnostics);
+        }
+        let ident = ast::ident_of(lval)?;
+        match self.ident_type(ident)? {
+            SymbolType::Variable { ty, .. } => {
+                let indexed = ty.index(indices.len());
+                if indexed.is_none() {
+                    let message = if ty.is_array() {
+                        format!("too many subscripts for '{}' of type '{}'", self.node_text(ident), ty)
+                    } else {
+                        format!("subscripted value '{}' is not an array", self.node_text(ident))
+                    };
+                    diagnostics.push(error(node_range(lval), message));
+                }
+                indexed
+            }
+            SymbolType::Function { .. } => {
+                diagnostics.push(error(node_range(ident), format!(
+                    "'{}' is a function, not a variable", self.node_text(ident))));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::DocHandler;
+
+    fn diagnose(text: &str) -> Vec<String> {
+        let doc = DocHandler::from_text(text);
+        let mut diagnostics = Vec::new();
+        doc.check_types(&mut diagnostics);
+        diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_well_typed() {
+        assert!(diagnose("int a[2][3]; int f(int x[][3]) { return x[0][1]; } \
+            int main() { a[1][2] = f(a) + getint(); putarray(3, a[0]); return 0; }").is_empty());
+    }
+
+    #[test]
+    fn test_type_errors() {
+        assert_eq!(diagnose("void f() {} int main() { int a; a = f() + 1; return 0; }"),
+            vec!["call to void function 'f' cannot be used as a value"]);
+        assert_eq!(diagnose("int main() { int a; return a[0]; }"),
+            vec!["subscripted value 'a' is not an array"]);
+        assert_eq!(diagnose("int main() { int a[2][3]; return a[1]; }"),
+            vec!["'a[1]' has array type 'int[3]' but an int is expected"]);
+        assert_eq!(diagnose("int main() { int a[2]; a = 1; return 0; }"),
+            vec!["cannot assign to array 'a' of type 'int[2]'"]);
+    }
+
+    #[test]
+    fn test_float_types() {
+        assert!(diagnose("float a[2] = {1, 2.5}; int f(float x) { return x * 2; } \
+            float main() { int i = 1.5; float s = a[i] + i / 2; if (s) s = -s; return f(i) + getfloat(); }").is_empty());
+        assert_eq!(diagnose("int main() { float a[2]; int b[4]; return b[a[0]] % 2.0; }"),
+            vec!["'a[0]' has type 'float' but an int is expected", "'2.0' has type 'float' but an int is expected"]);
+        assert_eq!(diagnose("float f(float a[]) { return a; }"),
+            vec!["'a' has array type 'float[]' but a float is expected"]);
+    }
+}
@@ -0,0 +1,73 @@
+use tower_lsp::lsp_types::Diagnostic;
+use tree_sitter::Node;
+
+use super::super::{ast, DocHandler, node_range, sylib, walk_tree};
+use super::error;
+
+impl DocHandler {
+    /// Report every `Lval` or `FuncCall` whose identifier does not resolve to a
+    /// declaration visible at that point
+    pub(super) fn check_undeclared(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let root = self.syntax_tree.root_node();
+        walk_tree(root, |n| {
+            if n.kind() != "Lval" && n.kind() != "FuncCall" {
+                return;
+            }
+            let Some(ident) = ast::ident_of(n) else {
+                return;
+            };
+            if self.resolve_ident(ident).is_some() {
+                return;
+            }
+            let name = self.node_text(ident);
+            // Using a runtime function as a value is reported by the type check
+            if sylib::is_runtime_function(&name) {
+                return;
+            }
+            let message = if n.kind() == "FuncCall" {
+                if self.is_defined_after(root, ident, &name) {
+                    format!("function '{}' is called before its definition", name)
+                } else {
+                    format!("call to undeclared function '{}'", name)
+                }
+            } else {
+                format!("use of undeclared identifier '{}'", name)
+            };
+            diagnostics.push(error(node_range(ident), message));
+        });
+    }
+
+    fn is_defined_after(&self, root: Node, ident: Node, name: &str) -> bool {
+        let Some(comp_unit) = root.named_child(0) else {
+            return false;
+        };
+        let mut cursor = comp_unit.walk();
+        let found = comp_unit.named_children(&mut cursor)
+            .filter(|item| item.kind() == "FuncDef" && item.start_byte() > ident.start_byte())
+            .filter_map(|item| item.child_by_field_name("ident"))
+            .any(|func_ident| self.node_text(func_ident) == name);
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::DocHandler;
+
+    fn diagnose(text: &str) -> Vec<String> {
+        let doc = DocHandler::from_text(text);
+        let mut diagnostics = Vec::new();
+        doc.check_undeclared(&mut diagnostics);
+        diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_undeclared() {
+        assert!(diagnose("int g; int f(int a) { return f(a + g); } int main() { putint(f(1)); return 0; }").is_empty());
+        assert_eq!(diagnose("int main() { { int a; } return a; }"), vec!["use of undeclared identifier 'a'"]);
+        assert_eq!(diagnose("int main() { return f(); } int f() { return 0; }"),
+            vec!["function 'f' is called before its definition"]);
+        assert_eq!(diagnose("int main() { return g(); }"), vec!["call to undeclared function 'g'"]);
+        assert_eq!(diagnose("int main() { int a = b, b = 1; return a; }"), vec!["use of undeclared identifier 'b'"]);
+    }
+}
@@ -48,7 +48,7 @@ impl DynText {
         let mut line_bytes = TreapList::new();
 
         text.lines().for_each(|line| {
-            let line_length = line.bytes().len();
+            let line_length = line.len();
             content.push(StringWrapper::from(format!("{}\n", line)));
             line_bytes.push(line_length + 1);
         });
@@ -92,7 +92,7 @@ impl DynText {
         let new_next = format!("{}{}{}", prefix, new_text, subfix);
         self.line_bytes.remove_range(input_edit.start_position.row..input_edit.old_end_position.row + 1);
         for (idx, line) in new_next.lines().enumerate() {
-            self.line_bytes.insert_after_k_nodes(input_edit.start_position.row + idx, line.bytes().len() + 1);
+            self.line_bytes.insert_after_k_nodes(input_edit.start_position.row + idx, line.len() + 1);
         }
         self.content.remove_range(input_edit.start_position.row..input_edit.old_end_position.row + 1);
         for (idx, line) in new_next.lines().enumerate() {
@@ -125,7 +125,7 @@ impl DynText {
             let line = self.content.get(start.row).unwrap_or(&EMPTY_STRING_WRAPPER);
             let start_byte = line.0.char_indices().nth(start.column).unwrap_or((0, '\0')).0;
             let end_byte = line.0.char_indices().nth(end.column).unwrap_or((line.0.len(), '\0')).0;
-            return line.0[start_byte..end_byte].to_string();
+            line.0[start_byte..end_byte].to_string()
         }
         else {
             let mut result = String::new();
@@ -154,7 +154,7 @@ impl DocHandler {
         let tower_lsp::lsp_types::Range{ start, end} = change.range.expect("Range should be defined");
         let start_byte = self.doc.get_byte_offset(start.line as usize, start.character as usize);
         let old_end_byte = self.doc.get_byte_offset(end.line as usize, end.character as usize);
-        let new_end_byte = start_byte + change.text.bytes().len();
+        let new_end_byte = start_byte + change.text.len();
         let text_lf_count = change.text.chars().filter(|&c| c == '\n').count();
         let new_end_row = start.line as usize + text_lf_count;
         let new_end_column = if text_lf_count > 0 {
@@ -191,6 +191,7 @@ impl DocHandler {
         }
         
         self.syntax_tree = new_tree;
+        *self.const_cache.get_mut().unwrap() = Default::default();
     }
 
     pub fn get_text_range(&self, start: Point, end: Point) -> String {
@@ -205,12 +206,13 @@ mod tests {
     impl DynText {
         pub fn sanity_check(&self, text: &str) {
             for (line_idx, line) in text.lines().enumerate() {
-                let line_length = line.bytes().len();
+                let line_length = line.len();
                 assert_eq!(*self.line_bytes.get(line_idx).unwrap(), line_length + 1);
                 assert_eq!(self.content.get(line_idx).unwrap().0, format!("{}\n", line));
             }
-            assert_eq!(self.content.len(), text.lines().count());
-            assert_eq!(self.line_bytes.len(), text.lines().count());
+            // `DynText` always keeps a trailing empty line after the last one
+            assert_eq!(self.content.len(), text.lines().count() + 1);
+            assert_eq!(self.line_bytes.len(), text.lines().count() + 1);
             assert_eq!(self.content.sum_range(0..self.content.len()).0.trim_end(), text.trim_end());
         }
     }
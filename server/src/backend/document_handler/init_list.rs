@@ -0,0 +1,82 @@
+//! Layout of `InitVal`/`ConstInitVal` lists over array dimensions, following
+//! the brace elision rules of SysY: a nested list initializes the largest
+//! sub-array that starts at the current position.
+
+use tree_sitter::Node;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitError {
+    /// More elements than the array can hold
+    ExcessElements,
+    /// A nested list where a scalar is expected
+    BracesAroundScalar,
+    /// A nested list that does not start at a sub-array boundary
+    Misaligned,
+    /// A single expression initializing an array
+    ScalarForArray,
+}
+
+#[derive(Debug, Default)]
+pub struct InitLayout<'a> {
+    /// Each initializing expression with its index in the flattened array
+    pub leaves: Vec<(usize, Node<'a>)>,
+    pub errors: Vec<(Node<'a>, InitError)>,
+}
+
+/// The expression of an initializer that is not a list
+fn scalar_exp(init: Node) -> Option<Node> {
+    init.named_child(0).filter(|c| c.kind() == "Exp" || c.kind() == "ConstExp")
+}
+
+/// The number of elements in an array, if it fits in memory at all
+pub fn array_size(dims: &[usize]) -> Option<usize> {
+    dims.iter().try_fold(1usize, |acc, dim| acc.checked_mul(*dim))
+}
+
+/// Lay out the initializer of a variable with array dimensions `dims`,
+/// which is empty for scalars
+pub fn layout<'a>(init: Node<'a>, dims: &[usize]) -> InitLayout<'a> {
+    let mut result = InitLayout::default();
+    if array_size(dims).is_none() {
+        return result;
+    }
+    match (scalar_exp(init), dims.is_empty()) {
+        (Some(exp), true) => result.leaves.push((0, exp)),
+        (None, true) => result.errors.push((init, InitError::BracesAroundScalar)),
+        (Some(_), false) => result.errors.push((init, InitError::ScalarForArray)),
+        (None, false) => layout_list(init, dims, 0, &mut result),
+    }
+    result
+}
+
+fn layout_list<'a>(list: Node<'a>, dims: &[usize], base: usize, result: &mut InitLayout<'a>) {
+    let size = |dims: &[usize]| dims.iter().product::<usize>();
+    let total = size(dims);
+    let mut pos = 0;
+    let mut cursor = list.walk();
+    let elems: Vec<_> = list.named_children(&mut cursor)
+        .filter(|c| c.kind() == "InitVal" || c.kind() == "ConstInitVal")
+        .collect();
+    for elem in elems {
+        if pos >= total {
+            result.errors.push((elem, InitError::ExcessElements));
+            return;
+        }
+        if let Some(exp) = scalar_exp(elem) {
+            result.leaves.push((base + pos, exp));
+            pos += 1;
+            continue;
+        }
+        if dims.len() == 1 {
+            result.errors.push((elem, InitError::BracesAroundScalar));
+            pos += 1;
+            continue;
+        }
+        let Some(level) = (1..dims.len()).find(|&level| pos % size(&dims[level..]) == 0) else {
+            result.errors.push((elem, InitError::Misaligned));
+            return;
+        };
+        layout_list(elem, &dims[level..], base + pos, result);
+        pos += size(&dims[level..]);
+    }
+}
@@ -2,12 +2,35 @@ mod incremental_update;
 mod query_definition;
 mod query_token;
 mod query_symbols;
+mod diagnostics;
+mod sylib;
+mod ast;
+mod types;
+mod const_eval;
+mod init_list;
+mod query_semantic_tokens;
+mod query_references;
+mod query_rename;
+mod query_highlight;
+mod query_document_symbols;
+mod query_call_hierarchy;
+mod query_selection_range;
+mod query_folding_range;
+mod query_signature_help;
+
+use tower_lsp::lsp_types::{Position, Range, SemanticToken};
 
 pub use query_symbols::SymbolKind;
+pub use sylib::{load_runtime_library, runtime_functions};
 pub struct DocHandler
 {
     syntax_tree: tree_sitter::Tree,
     doc: incremental_update::DynText,
+    /// The last semantic tokens sent to the client, with their result id
+    semantic_tokens: Option<(String, Vec<SemanticToken>)>,
+    semantic_tokens_version: u64,
+    /// Constant values computed for the current syntax tree
+    const_cache: std::sync::Mutex<const_eval::ConstCache>,
 }
 
 
@@ -17,7 +40,52 @@ impl DocHandler {
         let doc = incremental_update::DynText::new(content);
         DocHandler {
             syntax_tree: tree,
-            doc: doc,
+            doc,
+            semantic_tokens: None,
+            semantic_tokens_version: 0,
+            const_cache: Default::default(),
+        }
+    }
+
+    /// Parse `content` with a parser of its own
+    #[cfg(test)]
+    fn from_text(content: &str) -> Self {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_sysy_parser::LANGUAGE.into()).unwrap();
+        DocHandler::new(content, &mut parser)
+    }
+
+    fn node_text(&self, node: tree_sitter::Node) -> String {
+        self.doc.get_text_range(node.start_position(), node.end_position())
+    }
+}
+
+/// Convert the span of a syntax node into an LSP range
+fn node_range(node: tree_sitter::Node) -> Range {
+    Range {
+        start: Position {
+            line: node.start_position().row as u32,
+            character: node.start_position().column as u32,
+        },
+        end: Position {
+            line: node.end_position().row as u32,
+            character: node.end_position().column as u32,
+        },
+    }
+}
+
+/// Visit `root` and every node below it in document order
+fn walk_tree<'a>(root: tree_sitter::Node<'a>, mut visit: impl FnMut(tree_sitter::Node<'a>)) {
+    let mut cursor = root.walk();
+    loop {
+        visit(cursor.node());
+        if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return;
+            }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,220 @@
+use std::path::Path;
+
+use tower_lsp::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, LSPAny, Position, Range,
+    SymbolKind, Url,
+};
+use tree_sitter::Node;
+
+use super::query_references::Binding;
+use super::sylib;
+use super::{ast, node_range, walk_tree, DocHandler};
+
+/// Marks the call hierarchy items of runtime library functions, which are
+/// located in `sylib.h` rather than in the document
+const RUNTIME_ITEM_DATA: &str = "runtime";
+
+/// The function a call hierarchy item stands for
+#[derive(Debug, Clone, PartialEq)]
+enum Callee<'a> {
+    Function(Node<'a>),
+    /// A runtime library function, by name
+    Runtime(String),
+}
+
+/// The item of a runtime library function declared in the `sylib.h` at
+/// `header`. Built-in functions have no location and get no item.
+fn runtime_item(func: &sylib::RuntimeFunction, header: &Path) -> Option<CallHierarchyItem> {
+    let range = func.header_range?;
+    Some(CallHierarchyItem {
+        name: func.name.clone(),
+        kind: SymbolKind::FUNCTION,
+        tags: None,
+        detail: Some(func.signature()),
+        uri: Url::from_file_path(header).ok()?,
+        range,
+        selection_range: range,
+        data: Some(LSPAny::String(RUNTIME_ITEM_DATA.to_string())),
+    })
+}
+
+impl DocHandler {
+    /// The function called by a `FuncCall`. Calls to functions defined later
+    /// in the document are not in scope, but still count so that mutually
+    /// recursive functions show up in the hierarchy.
+    fn callee<'a>(&'a self, call: Node<'a>) -> Option<Callee<'a>> {
+        let ident = ast::ident_of(call)?;
+        match self.binding(ident) {
+            Some(Binding::Declared(decl)) => {
+                let func = decl.parent()?;
+                (func.kind() == "FuncDef").then_some(Callee::Function(func))
+            }
+            Some(Binding::Runtime(name)) => Some(Callee::Runtime(name)),
+            None => {
+                let name = self.node_text(ident);
+                self.top_level_functions().into_iter()
+                    .find(|f| f.child_by_field_name("ident").is_some_and(|i| self.node_text(i) == name))
+                    .map(Callee::Function)
+            }
+        }
+    }
+
+    fn callee_item(&self, uri: &Url, callee: Callee) -> Option<CallHierarchyItem> {
+        Some(match callee {
+            Callee::Function(func) => {
+                let ident = func.child_by_field_name("ident")?;
+                CallHierarchyItem {
+                    name: self.node_text(ident),
+                    kind: SymbolKind::FUNCTION,
+                    tags: None,
+                    detail: self.function_detail(func),
+                    uri: uri.clone(),
+                    range: node_range(func),
+                    selection_range: node_range(ident),
+                    data: None,
+                }
+            }
+            Callee::Runtime(name) => {
+                let func = sylib::runtime_function(&name)?;
+                runtime_item(&func, &sylib::runtime_header()?)?
+            }
+        })
+    }
+
+    /// The function an item returned by `prepare_call_hierarchy` stands for
+    fn item_callee(&self, item: &CallHierarchyItem) -> Option<Callee<'_>> {
+        if item.data == Some(LSPAny::String(RUNTIME_ITEM_DATA.to_string())) {
+            return sylib::is_runtime_function(&item.name).then(|| Callee::Runtime(item.name.clone()));
+        }
+        let func = self.ident_at(item.selection_range.start)?.parent()?;
+        (func.kind() == "FuncDef").then_some(Callee::Function(func))
+    }
+
+    /// Every `FuncCall` below `root` with the function it calls
+    fn calls_in<'a>(&'a self, root: Node<'a>) -> Vec<(Node<'a>, Callee<'a>)> {
+        let mut calls = Vec::new();
+        walk_tree(root, |n| {
+            if n.kind() == "FuncCall" {
+                if let Some(callee) = self.callee(n) {
+                    calls.push((n, callee));
+                }
+            }
+        });
+        calls
+    }
+
+    /// The function declared or called at `pos`
+    pub fn prepare_call_hierarchy(&self, uri: &Url, pos: Position) -> Option<Vec<CallHierarchyItem>> {
+        let ident = self.ident_at(pos)?;
+        let parent = ident.parent()?;
+        let callee = match parent.kind() {
+            "FuncDef" => Callee::Function(parent),
+            "FuncCall" => self.callee(parent)?,
+            _ => return None,
+        };
+        Some(vec![self.callee_item(uri, callee)?])
+    }
+
+    /// The functions calling `item`, with the ranges of their calls
+    pub fn incoming_calls(&self, uri: &Url, item: &CallHierarchyItem) -> Vec<CallHierarchyIncomingCall> {
+        let Some(target) = self.item_callee(item) else {
+            return Vec::new();
+        };
+        self.top_level_functions().into_iter()
+            .filter_map(|func| {
+                let from_ranges: Vec<_> = self.calls_in(func).into_iter()
+                    .filter(|(_, callee)| *callee == target)
+                    .filter_map(|(call, _)| ast::ident_of(call).map(node_range))
+                    .collect();
+                if from_ranges.is_empty() {
+                    return None;
+                }
+                Some(CallHierarchyIncomingCall {
+                    from: self.callee_item(uri, Callee::Function(func))?,
+                    from_ranges,
+                })
+            })
+            .collect()
+    }
+
+    /// The functions called by `item`, with the ranges of the calls
+    pub fn outgoing_calls(&self, uri: &Url, item: &CallHierarchyItem) -> Vec<CallHierarchyOutgoingCall> {
+        let Some(Callee::Function(func)) = self.item_callee(item) else {
+            return Vec::new();
+        };
+        let mut grouped: Vec<(Callee, Vec<Range>)> = Vec::new();
+        for (call, callee) in self.calls_in(func) {
+            let Some(ident) = ast::ident_of(call) else {
+                continue;
+            };
+            match grouped.iter_mut().find(|(c, _)| *c == callee) {
+                Some((_, ranges)) => ranges.push(node_range(ident)),
+                None => grouped.push((callee, vec![node_range(ident)])),
+            }
+        }
+        grouped.into_iter()
+            .filter_map(|(callee, from_ranges)| Some(CallHierarchyOutgoingCall {
+                to: self.callee_item(uri, callee)?,
+                from_ranges,
+            }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use tower_lsp::lsp_types::{Position, Url};
+
+    use super::super::{sylib, DocHandler};
+    use super::runtime_item;
+
+    #[test]
+    fn test_call_hierarchy() {
+        let doc = DocHandler::from_text("int even(int n) { if (n == 0) return 1; return odd(n - 1); }\n\
+                                         int odd(int n) { if (n == 0) return 0; return even(n - 1); }\n\
+                                         int fact(int n) { if (n) return n * fact(n - 1); return 1; }\n\
+                                         int main() { putint(even(fact(3))); putint(odd(4)); return 0; }");
+        let uri = Url::parse("file:///test.sy").unwrap();
+        let item = |line, character| doc.prepare_call_hierarchy(&uri, Position { line, character }).unwrap().remove(0);
+
+        let even = item(0, 4);
+        assert_eq!(even.detail.as_deref(), Some("int (int)"));
+        let callers: Vec<_> = doc.incoming_calls(&uri, &even).into_iter().map(|c| c.from.name).collect();
+        assert_eq!(callers, vec!["odd", "main"]);
+        let callees: Vec<_> = doc.outgoing_calls(&uri, &even).into_iter().map(|c| c.to.name).collect();
+        assert_eq!(callees, vec!["odd"]);
+
+        let fact = item(2, 4);
+        let callers: Vec<_> = doc.incoming_calls(&uri, &fact).into_iter().map(|c| c.from.name).collect();
+        assert_eq!(callers, vec!["fact", "main"]);
+
+        let main = item(3, 4);
+        let callees: Vec<_> = doc.outgoing_calls(&uri, &main).into_iter()
+            .map(|c| (c.to.name, c.from_ranges.len()))
+            .collect();
+        // The built-in runtime library has no `sylib.h` to point at
+        assert_eq!(callees, vec![("even".to_string(), 1), ("fact".to_string(), 1), ("odd".to_string(), 1)]);
+        assert!(doc.prepare_call_hierarchy(&uri, Position { line: 3, character: 14 }).is_none());
+    }
+
+    #[test]
+    fn test_runtime_item() {
+        let functions = sylib::parse_header("/* Input & output functions */\n\
+                                             int getint();\n\
+                                             void putint(int a);");
+        let putint = runtime_item(&functions[1], Path::new("/lib/sylib.h")).unwrap();
+        assert_eq!(putint.uri.as_str(), "file:///lib/sylib.h");
+        assert_eq!(putint.detail.as_deref(), Some("void (int)"));
+        assert_eq!((putint.selection_range.start.line, putint.selection_range.start.character), (2, 5));
+        assert!(runtime_item(&sylib::runtime_function("putint").unwrap(), Path::new("/lib/sylib.h")).is_none());
+
+        let doc = DocHandler::from_text("int main() { putint(getint()); putint(1); return 0; }");
+        let uri = Url::parse("file:///test.sy").unwrap();
+        let callers: Vec<_> = doc.incoming_calls(&uri, &putint).into_iter()
+            .map(|c| (c.from.name, c.from_ranges.len()))
+            .collect();
+        assert_eq!(callers, vec![("main".to_string(), 2)]);
+    }
+}
@@ -1,9 +1,15 @@
 
-use super::DocHandler;
+use super::query_references::Binding;
+use super::sylib::{self, RuntimeFunction};
+use super::{node_range, DocHandler};
 use tower_lsp::lsp_types::{Position, Range};
-use tree_sitter::{Node, Point};
+use tree_sitter::Node;
 
-fn find_definition<'a>(ident: &'a str, mut n: tree_sitter::Node<'a>, get_text_range: &'a impl Fn(tree_sitter::Point, tree_sitter::Point) -> String)
+/// Find the declaration of `ident` by walking back through the preceding
+/// siblings of `n` and then its ancestors. Statements such as `IfStmt` declare
+/// nothing themselves and the blocks nested in them are only entered from
+/// below, so their locals go out of scope where the statement ends.
+pub(super) fn find_definition<'a>(ident: &str, mut n: tree_sitter::Node<'a>, get_text_range: &impl Fn(tree_sitter::Point, tree_sitter::Point) -> String)
 -> Option<Node<'a>>
 {
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -11,9 +17,10 @@ fn find_definition<'a>(ident: &'a str, mut n: tree_sitter::Node<'a>, get_text_ra
     {
         FromSibling,
         FromChild,
-        NoJump,
     }
-    let mut last_jump = LastJump::NoJump;
+    let mut last_jump;
+    // Declarators after the use are not in scope yet, even in the same declaration
+    let use_start = n.start_byte();
     loop {
         if n.prev_named_sibling().is_some() {
             n = n.prev_named_sibling().unwrap();
@@ -32,7 +39,7 @@ fn find_definition<'a>(ident: &'a str, mut n: tree_sitter::Node<'a>, get_text_ra
                 if let Some(def_ident) = def.child_by_field_name("ident") {
                     let def_name = get_text_range(
                         def_ident.start_position(), def_ident.end_position());
-                    if def_name == ident {
+                    if def_name == ident && def.start_byte() < use_start {
                         return Some(n);
                     }
                 }
@@ -71,38 +78,124 @@ fn find_definition<'a>(ident: &'a str, mut n: tree_sitter::Node<'a>, get_text_ra
 }
 
 impl DocHandler {
-    pub fn find_definition(&self, pos: Position) -> Option<Range>
-    {
-        let node = self.syntax_tree.root_node().descendant_for_point_range(
-            Point {
-                row: pos.line as usize,
-                column: pos.character as usize,
-            },
-            Point {
-                row: pos.line as usize,
-                column: pos.character as usize + 1,
-            },
-        )?;
-        if node.kind() != "Ident"
-        {
+    /// Resolve an `Ident` node to the node that declares it, if any
+    pub(super) fn resolve_ident<'a>(&'a self, ident: Node<'a>) -> Option<Node<'a>> {
+        let name = self.node_text(ident);
+        find_definition(name.as_str(), ident, &|start, end| self.doc.get_text_range(start, end))
+    }
+
+    /// The `VarDef` or `ConstDef` declaring `name` inside a `VarDecl` or `ConstDecl`
+    pub(super) fn declarator<'a>(&self, decl: Node<'a>, name: &str) -> Option<Node<'a>> {
+        let mut cursor = decl.walk();
+        let def = decl.children_by_field_name("defs", &mut cursor)
+            .find(|d| d.child_by_field_name("ident").is_some_and(|i| self.node_text(i) == name));
+        def
+    }
+
+    /// The declaration that the identifier at `pos` refers to
+    pub fn find_definition(&self, pos: Position) -> Option<Definition> {
+        let ident = self.ident_at(pos)?;
+        let Binding::Declared(target) = self.binding(ident)? else {
             return None;
-        }
-        let name = self.doc.get_text_range(node.start_position(), node.end_position());
-        let get_text = |start: Point, end: Point| self.doc.get_text_range(start, end);
-        let definition = {
-            find_definition(name.as_str(), node, &get_text)
-        }?;
-        Some(
-            Range {
-                start: Position {
-                    line: definition.start_position().row as u32,
-                    character: definition.start_position().column as u32,
-                },
-                end: Position {
-                    line: definition.end_position().row as u32,
-                    character: definition.end_position().column as u32,
-                },
+        };
+        Some(Definition {
+            origin_selection_range: node_range(ident),
+            target_range: node_range(target.parent()?),
+            target_selection_range: node_range(target),
+        })
+    }
+
+    /// The source of the declaration that the identifier at `pos` refers to.
+    /// Variables and constants show their own declarator with the type of the
+    /// declaration, rather than every declarator sharing it.
+    pub fn definition_text(&self, pos: Position) -> Option<String> {
+        let ident = self.ident_at(pos)?;
+        let Binding::Declared(target) = self.binding(ident)? else {
+            return None;
+        };
+        let def = target.parent()?;
+        match def.kind() {
+            "VarDef" | "ConstDef" => {
+                let decl = def.parent()?;
+                let const_keyword = if decl.kind() == "ConstDecl" { "const " } else { "" };
+                let ty = self.node_text(decl.child_by_field_name("type")?);
+                Some(format!("{}{} {}", const_keyword, ty, self.node_text(def)))
             }
-        )
+            _ => Some(self.node_text(def)),
+        }
+    }
+
+    /// The runtime library function called by the identifier at `pos`
+    pub fn runtime_function_at(&self, pos: Position) -> Option<RuntimeFunction> {
+        let ident = self.ident_at(pos)?;
+        let Binding::Runtime(name) = self.binding(ident)? else {
+            return None;
+        };
+        sylib::runtime_function(&name)
     }
-}
\ No newline at end of file
+}
+
+/// Where a symbol is declared, in the shape of an LSP `LocationLink`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    /// The identifier the definition was requested for
+    pub origin_selection_range: Range,
+    /// The `VarDef`, `ConstDef`, `FuncFParam` or `FuncDef` declaring the symbol
+    pub target_range: Range,
+    /// The identifier in the declaration
+    pub target_selection_range: Range,
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Position;
+
+    use super::super::DocHandler;
+
+    #[test]
+    fn test_find_definition() {
+        let doc = DocHandler::from_text("int a, b[10] = {1}, c;\nint f(int x) { return c + b[x] + f(x); }");
+        let span = |r: tower_lsp::lsp_types::Range| (r.start.line, r.start.character, r.end.character);
+
+        let c = doc.find_definition(Position { line: 1, character: 22 }).unwrap();
+        assert_eq!(span(c.origin_selection_range), (1, 22, 23));
+        assert_eq!(span(c.target_range), (0, 20, 21));
+        assert_eq!(span(c.target_selection_range), (0, 20, 21));
+        let b = doc.find_definition(Position { line: 1, character: 26 }).unwrap();
+        assert_eq!(span(b.target_range), (0, 7, 18));
+        assert_eq!(span(b.target_selection_range), (0, 7, 8));
+
+        assert_eq!(doc.definition_text(Position { line: 1, character: 26 }).as_deref(), Some("int b[10] = {1}"));
+        assert_eq!(doc.definition_text(Position { line: 1, character: 28 }).as_deref(), Some("int x"));
+        assert!(doc.definition_text(Position { line: 1, character: 33 }).unwrap().starts_with("int f(int x) {"));
+        assert!(doc.runtime_function_at(Position { line: 1, character: 33 }).is_none());
+    }
+
+    #[test]
+    fn test_find_definition_in_statements() {
+        let doc = DocHandler::from_text("int a;\nint f(int b) {\n    if (b) { int a; a = 1; } else a = b;\n    while (a) { int b; b = a; }\n    return a;\n}");
+        let target = |line, character| doc.find_definition(Position { line, character }).map(|d| {
+            let r = d.target_selection_range;
+            (r.start.line, r.start.character)
+        });
+        // The local in the consequence does not reach the alternative or later statements
+        assert_eq!(target(2, 20), Some((2, 17)));
+        assert_eq!(target(2, 34), Some((0, 4)));
+        assert_eq!(target(2, 38), Some((1, 10)));
+        assert_eq!(target(3, 23), Some((3, 20)));
+        assert_eq!(target(3, 27), Some((0, 4)));
+        assert_eq!(target(4, 11), Some((0, 4)));
+    }
+
+    #[test]
+    fn test_runtime_function_at() {
+        let doc = DocHandler::from_text("int main() { putarray(1, getint()); int putint; return putint; }");
+        let putarray = doc.runtime_function_at(Position { line: 0, character: 14 }).unwrap();
+        assert_eq!(putarray.declaration(), "void putarray(int n, int a[])");
+        assert!(putarray.doc.is_some());
+        assert!(doc.definition_text(Position { line: 0, character: 14 }).is_none());
+        assert_eq!(doc.runtime_function_at(Position { line: 0, character: 26 }).map(|f| f.name).as_deref(), Some("getint"));
+        // A local variable shadows the runtime function of the same name
+        assert!(doc.runtime_function_at(Position { line: 0, character: 58 }).is_none());
+    }
+}
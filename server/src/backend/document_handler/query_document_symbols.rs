@@ -0,0 +1,149 @@
+use tower_lsp::lsp_types::{DocumentSymbol, Location, SymbolInformation, Url};
+use tree_sitter::Node;
+
+use super::query_symbols::{declared_idents, SymbolKind};
+use super::{node_range, DocHandler};
+
+impl DocHandler {
+    /// The outline of the document: globals, constants and functions, with
+    /// the parameters and locals of each function nested under it
+    pub fn document_symbols(&self) -> Vec<DocumentSymbol> {
+        let Some(comp_unit) = self.syntax_tree.root_node().named_child(0) else {
+            return Vec::new();
+        };
+        let mut cursor = comp_unit.walk();
+        let items: Vec<_> = comp_unit.named_children(&mut cursor).collect();
+        items.into_iter()
+            .flat_map(|item| match item.kind() {
+                "FuncDef" => self.function_symbol(item).into_iter().collect(),
+                _ => self.declaration_symbols(item),
+            })
+            .collect()
+    }
+
+    /// The top-level symbols of the document, for searching the workspace
+    pub fn workspace_symbols(&self, uri: &Url) -> Vec<SymbolInformation> {
+        self.document_symbols().into_iter()
+            .map(|symbol| {
+                #[allow(deprecated)]
+                SymbolInformation {
+                    name: symbol.name,
+                    kind: symbol.kind,
+                    tags: None,
+                    deprecated: None,
+                    location: Location {
+                        uri: uri.clone(),
+                        range: symbol.selection_range,
+                    },
+                    container_name: None,
+                }
+            })
+            .collect()
+    }
+
+    fn function_symbol(&self, func: Node) -> Option<DocumentSymbol> {
+        let ident = func.child_by_field_name("ident")?;
+        let mut children = Vec::new();
+        let mut cursor = func.walk();
+        for child in func.named_children(&mut cursor) {
+            match child.kind() {
+                "FuncFParam" => children.extend(self.declaration_symbols(child)),
+                "Block" => self.block_symbols(child, &mut children),
+                _ => {}
+            }
+        }
+        self.symbol(ident, func, self.function_detail(func), children)
+    }
+
+    /// The locals of a `Block` and of the blocks nested in it, including
+    /// those under `if` and `while`, in order
+    fn block_symbols(&self, block: Node, symbols: &mut Vec<DocumentSymbol>) {
+        let mut cursor = block.walk();
+        for child in block.named_children(&mut cursor) {
+            match child.kind() {
+                "Block" | "IfStmt" | "WhileStmt" => self.block_symbols(child, symbols),
+                _ => symbols.extend(self.declaration_symbols(child)),
+            }
+        }
+    }
+
+    /// One symbol per identifier of a `VarDecl`, `ConstDecl` or `FuncFParam`
+    fn declaration_symbols(&self, decl: Node) -> Vec<DocumentSymbol> {
+        declared_idents(decl).into_iter()
+            .filter_map(|ident| {
+                let def = ident.parent()?;
+                let ty = match def.kind() {
+                    "FuncFParam" => self.param_type(def),
+                    _ => self.def_type(def),
+                };
+                self.symbol(ident, def, ty.map(|ty| ty.to_string()), Vec::new())
+            })
+            .collect()
+    }
+
+    /// A signature such as `int (int, int[])`
+    pub(super) fn function_detail(&self, func: Node) -> Option<String> {
+        let ret = self.base_type(func.child_by_field_name("type")?)?;
+        let mut cursor = func.walk();
+        let params = func.children_by_field_name("params", &mut cursor)
+            .map(|p| self.param_type(p).map(|ty| ty.to_string()))
+            .collect::<Option<Vec<_>>>()?;
+        Some(format!("{} ({})", ret, params.join(", ")))
+    }
+
+    fn symbol(&self, ident: Node, range: Node, detail: Option<String>, children: Vec<DocumentSymbol>)
+    -> Option<DocumentSymbol> {
+        let name = self.node_text(ident);
+        // Identifiers inserted by error recovery are empty, which clients reject
+        if name.is_empty() {
+            return None;
+        }
+        #[allow(deprecated)]
+        Some(DocumentSymbol {
+            name,
+            detail,
+            kind: SymbolKind::of_declaration(ident)?.to_lsp(),
+            tags: None,
+            deprecated: None,
+            range: node_range(range),
+            selection_range: node_range(ident),
+            children: (!children.is_empty()).then_some(children),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{DocumentSymbol, SymbolKind};
+
+    use super::super::DocHandler;
+
+    fn outline(symbols: &[DocumentSymbol], depth: usize, lines: &mut Vec<String>) {
+        for symbol in symbols {
+            lines.push(format!("{}{} {:?} {}", "  ".repeat(depth), symbol.name,
+                symbol.kind, symbol.detail.as_deref().unwrap_or("")));
+            outline(symbol.children.as_deref().unwrap_or_default(), depth + 1, lines);
+        }
+    }
+
+    #[test]
+    fn test_document_symbols() {
+        let doc = DocHandler::from_text("const int N = 10; const int M = N * 2;\n\
+                                         int a[N][M];\n\
+                                         int f(int x, int b[][M]) { int y; while (x) { int z[2]; } return y; }\n\
+                                         void g() {}");
+        let mut lines = Vec::new();
+        outline(&doc.document_symbols(), 0, &mut lines);
+        assert_eq!(lines, vec![
+            format!("N {:?} int", SymbolKind::CONSTANT),
+            format!("M {:?} int", SymbolKind::CONSTANT),
+            format!("a {:?} int[10][20]", SymbolKind::ARRAY),
+            format!("f {:?} int (int, int[][20])", SymbolKind::FUNCTION),
+            format!("  x {:?} int", SymbolKind::VARIABLE),
+            format!("  b {:?} int[][20]", SymbolKind::VARIABLE),
+            format!("  y {:?} int", SymbolKind::VARIABLE),
+            format!("  z {:?} int[2]", SymbolKind::ARRAY),
+            format!("g {:?} void ()", SymbolKind::FUNCTION),
+        ]);
+    }
+}
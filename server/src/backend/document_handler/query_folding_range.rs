@@ -0,0 +1,98 @@
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind};
+use tree_sitter::{Node, Point};
+
+use super::{walk_tree, DocHandler};
+
+fn folding_range(start_line: usize, end_line: usize, kind: Option<FoldingRangeKind>) -> Option<FoldingRange> {
+    (end_line > start_line).then_some(FoldingRange {
+        start_line: start_line as u32,
+        start_character: None,
+        end_line: end_line as u32,
+        end_character: None,
+        kind,
+        collapsed_text: None,
+    })
+}
+
+impl DocHandler {
+    /// Whether only whitespace precedes `point` on its line
+    fn starts_line(&self, point: Point) -> bool {
+        self.doc.get_text_range(Point { row: point.row, column: 0 }, point).trim().is_empty()
+    }
+
+    /// Fold a braced node, keeping its closing brace visible when the brace
+    /// is on a line of its own
+    fn brace_folding_range(&self, node: Node) -> Option<FoldingRange> {
+        let close = node.child(node.child_count().checked_sub(1)?).filter(|c| c.kind() == "}")?;
+        let end_line = if self.starts_line(close.start_position()) {
+            close.start_position().row.checked_sub(1)?
+        } else {
+            close.start_position().row
+        };
+        folding_range(node.start_position().row, end_line, None)
+    }
+
+    /// Foldable regions: blocks, comments and multi-line initializer lists
+    pub fn folding_ranges(&self) -> Vec<FoldingRange> {
+        let mut ranges = Vec::new();
+        // The first and last line of the current run of `//` comments
+        let mut line_comments: Option<(usize, usize)> = None;
+        walk_tree(self.syntax_tree.root_node(), |n| match n.kind() {
+            "Block" => ranges.extend(self.brace_folding_range(n)),
+            "InitVal" | "ConstInitVal" if n.child(0).is_some_and(|c| c.kind() == "{") => {
+                ranges.extend(self.brace_folding_range(n));
+            }
+            "comment" => {
+                let (start, end) = (n.start_position(), n.end_position());
+                if start.row != end.row {
+                    ranges.extend(folding_range(start.row, end.row, Some(FoldingRangeKind::Comment)));
+                    return;
+                }
+                if !self.node_text(n).starts_with("//") || !self.starts_line(start) {
+                    return;
+                }
+                line_comments = match line_comments {
+                    Some((first, last)) if last + 1 == start.row => Some((first, start.row)),
+                    Some((first, last)) => {
+                        ranges.extend(folding_range(first, last, Some(FoldingRangeKind::Comment)));
+                        Some((start.row, start.row))
+                    }
+                    None => Some((start.row, start.row)),
+                };
+            }
+            _ => {}
+        });
+        if let Some((first, last)) = line_comments {
+            ranges.extend(folding_range(first, last, Some(FoldingRangeKind::Comment)));
+        }
+        ranges.sort_by_key(|r| (r.start_line, std::cmp::Reverse(r.end_line)));
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::FoldingRangeKind;
+
+    use super::super::DocHandler;
+
+    #[test]
+    fn test_folding_ranges() {
+        let doc = DocHandler::from_text("// Header\n\
+                                         // comment\n\
+                                         int a[2][2] = {\n\
+                                         \x20   {1, 2},\n\
+                                         \x20   {3, 4}};\n\
+                                         /* block\n\
+                                         \x20  comment */\n\
+                                         int main() {\n\
+                                         \x20   int b = 0; // trailing\n\
+                                         \x20   int c = 0; // comments\n\
+                                         \x20   return 0;\n\
+                                         }");
+        let ranges: Vec<_> = doc.folding_ranges().iter()
+            .map(|r| (r.start_line, r.end_line, r.kind == Some(FoldingRangeKind::Comment)))
+            .collect();
+        assert_eq!(ranges, vec![(0, 1, true), (2, 4, false), (5, 6, true), (7, 10, false)]);
+    }
+}
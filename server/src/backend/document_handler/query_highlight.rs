@@ -0,0 +1,49 @@
+use tower_lsp::lsp_types::{DocumentHighlight, DocumentHighlightKind, Position};
+use tree_sitter::Node;
+
+use super::{ast, node_range, DocHandler};
+
+/// Whether an occurrence of a symbol stores a value into it
+fn is_write(ident: Node) -> bool {
+    ident.parent().is_some_and(|p| match p.kind() {
+        "VarDef" | "ConstDef" => p.child_by_field_name("ident") == Some(ident),
+        "Lval" => ast::is_assign_target(p),
+        _ => false,
+    })
+}
+
+impl DocHandler {
+    /// Every occurrence of the symbol at `pos`, marking where it is written
+    pub fn document_highlights(&self, pos: Position) -> Vec<DocumentHighlight> {
+        let Some(ident) = self.ident_at(pos) else {
+            return Vec::new();
+        };
+        self.bound_idents(ident).into_iter()
+            .map(|n| DocumentHighlight {
+                range: node_range(n),
+                kind: Some(if is_write(n) { DocumentHighlightKind::WRITE } else { DocumentHighlightKind::READ }),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{DocumentHighlightKind, Position};
+
+    use super::super::DocHandler;
+
+    #[test]
+    fn test_document_highlights() {
+        let doc = DocHandler::from_text("int f(int a[]) { int b = 0; a[b] = b; if (b) b = a[0]; return b; }");
+        let highlights: Vec<_> = doc.document_highlights(Position { line: 0, character: 21 }).iter()
+            .map(|h| (h.range.start.character, h.kind == Some(DocumentHighlightKind::WRITE)))
+            .collect();
+        assert_eq!(highlights, vec![(21, true), (30, false), (35, false), (42, false), (45, true), (62, false)]);
+        // Writing to an element of an array parameter
+        let highlights: Vec<_> = doc.document_highlights(Position { line: 0, character: 10 }).iter()
+            .map(|h| (h.range.start.character, h.kind == Some(DocumentHighlightKind::WRITE)))
+            .collect();
+        assert_eq!(highlights, vec![(10, false), (28, true), (49, false)]);
+    }
+}
@@ -0,0 +1,127 @@
+use tower_lsp::lsp_types::{Position, Range};
+use tree_sitter::{Node, Point};
+
+use super::query_definition::find_definition;
+use super::query_symbols::declared_idents;
+use super::{node_range, sylib, walk_tree, DocHandler};
+
+/// What an identifier refers to, used to tell apart symbols sharing a name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Binding<'a> {
+    /// A symbol of the document, identified by the `Ident` declaring it
+    Declared(Node<'a>),
+    /// A function of the runtime library, which has no declaration
+    Runtime(String),
+}
+
+impl DocHandler {
+    /// The `Ident` under the cursor, also accepting a cursor right after it
+    pub(super) fn ident_at(&self, pos: Position) -> Option<Node<'_>> {
+        let root = self.syntax_tree.root_node();
+        let point = Point {
+            row: pos.line as usize,
+            column: pos.character as usize,
+        };
+        let at = |start: Point, end: Point| {
+            root.descendant_for_point_range(start, end).filter(|n| n.kind() == "Ident")
+        };
+        at(point, Point { column: point.column + 1, ..point }).or_else(|| {
+            let before = Point { column: point.column.checked_sub(1)?, ..point };
+            at(before, point)
+        })
+    }
+
+    /// Whether an `Ident` is the name given by a declaration rather than a use
+    pub(super) fn is_declaring_ident(ident: Node) -> bool {
+        ident.parent().is_some_and(|p| {
+            matches!(p.kind(), "VarDef" | "ConstDef" | "FuncDef" | "FuncFParam")
+                && p.child_by_field_name("ident") == Some(ident)
+        })
+    }
+
+    /// Resolve an `Ident` to the symbol it declares or refers to
+    pub(super) fn binding<'a>(&'a self, ident: Node<'a>) -> Option<Binding<'a>> {
+        self.binding_with(ident, &|start, end| self.doc.get_text_range(start, end))
+    }
+
+    /// Like `binding`, but reading names through `get_text_range`, so that
+    /// callers can ask how an identifier would resolve after an edit
+    pub(super) fn binding_with<'a>(&'a self, ident: Node<'a>, get_text_range: &impl Fn(Point, Point) -> String)
+    -> Option<Binding<'a>> {
+        if Self::is_declaring_ident(ident) {
+            return Some(Binding::Declared(ident));
+        }
+        let text = |n: Node| get_text_range(n.start_position(), n.end_position());
+        let name = text(ident);
+        let Some(def) = find_definition(&name, ident, get_text_range) else {
+            let is_call = ident.parent().is_some_and(|p| p.kind() == "FuncCall");
+            return (is_call && sylib::is_runtime_function(&name)).then_some(Binding::Runtime(name));
+        };
+        match def.kind() {
+            "VarDecl" | "ConstDecl" => declared_idents(def).into_iter().find(|i| text(*i) == name),
+            _ => def.child_by_field_name("ident"),
+        }
+        .map(Binding::Declared)
+    }
+
+    /// Every `Ident` bound to the same symbol as `ident`, in document order
+    pub(super) fn bound_idents<'a>(&'a self, ident: Node<'a>) -> Vec<Node<'a>> {
+        let Some(binding) = self.binding(ident) else {
+            return Vec::new();
+        };
+        let name = self.node_text(ident);
+        let mut idents = Vec::new();
+        walk_tree(self.syntax_tree.root_node(), |n| {
+            if n.kind() == "Ident" && self.node_text(n) == name && self.binding(n).as_ref() == Some(&binding) {
+                idents.push(n);
+            }
+        });
+        idents
+    }
+
+    /// The ranges of all occurrences of the symbol at `pos`
+    pub fn find_references(&self, pos: Position, include_declaration: bool) -> Vec<Range> {
+        let Some(ident) = self.ident_at(pos) else {
+            return Vec::new();
+        };
+        self.bound_idents(ident).into_iter()
+            .filter(|n| include_declaration || !Self::is_declaring_ident(*n))
+            .map(node_range)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Position;
+
+    use super::super::DocHandler;
+
+    /// The positions of the references to the symbol at `line:character`
+    fn references(text: &str, line: u32, character: u32, include_declaration: bool) -> Vec<(u32, u32)> {
+        let doc = DocHandler::from_text(text);
+        doc.find_references(Position { line, character }, include_declaration).iter()
+            .map(|r| (r.start.line, r.start.character))
+            .collect()
+    }
+
+    #[test]
+    fn test_references() {
+        let text = "int a = 1;\n\
+                    int f(int a) {\n\
+                    \x20   a = a + 1;\n\
+                    \x20   { int a = 2; a = 3; }\n\
+                    \x20   return a;\n\
+                    }\n\
+                    int main() { a = f(a); putint(a); putint(f(1)); return 0; }";
+        // The global is only used in main
+        assert_eq!(references(text, 0, 4, true), vec![(0, 4), (6, 13), (6, 19), (6, 30)]);
+        assert_eq!(references(text, 6, 13, false), vec![(6, 13), (6, 19), (6, 30)]);
+        // The parameter is shadowed in the inner block
+        assert_eq!(references(text, 2, 8, true), vec![(1, 10), (2, 4), (2, 8), (4, 11)]);
+        assert_eq!(references(text, 3, 17, false), vec![(3, 17)]);
+        // Functions, including runtime ones, and a cursor right after the name
+        assert_eq!(references(text, 1, 5, false), vec![(6, 17), (6, 41)]);
+        assert_eq!(references(text, 6, 29, true), vec![(6, 23), (6, 34)]);
+    }
+}
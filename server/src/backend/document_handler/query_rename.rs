@@ -0,0 +1,110 @@
+use tower_lsp::lsp_types::{Position, Range};
+use tree_sitter::{Node, Point};
+
+use super::query_references::Binding;
+use super::query_symbols::{declaring_scope, scope_idents};
+use super::{node_range, walk_tree, DocHandler};
+
+impl DocHandler {
+    /// The `Ident` at `pos` with every occurrence bound to the same symbol,
+    /// if that symbol can be renamed
+    fn renamable_idents(&self, pos: Position) -> Result<(Node<'_>, Vec<Node<'_>>), String> {
+        let ident = self.ident_at(pos).ok_or("no symbol to rename at this position")?;
+        let name = self.node_text(ident);
+        match self.binding(ident) {
+            Some(Binding::Declared(_)) => Ok((ident, self.bound_idents(ident))),
+            Some(Binding::Runtime(_)) => Err(format!("cannot rename runtime library function '{}'", name)),
+            None => Err(format!("'{}' does not refer to a declaration", name)),
+        }
+    }
+
+    /// The range of the symbol that a rename at `pos` would change
+    pub fn prepare_rename(&self, pos: Position) -> Result<Range, String> {
+        self.renamable_idents(pos).map(|(ident, _)| node_range(ident))
+    }
+
+    /// The ranges to replace with `new_name` to rename the symbol at `pos`.
+    /// The rename is refused when any occurrence of the symbol, or any
+    /// identifier already named `new_name`, would resolve differently.
+    pub fn rename(&self, pos: Position, new_name: &str) -> Result<Vec<Range>, String> {
+        let (ident, occurrences) = self.renamable_idents(pos)?;
+        let old_name = self.node_text(ident);
+        if old_name == new_name {
+            return Ok(occurrences.into_iter().map(node_range).collect());
+        }
+
+        for declaration in occurrences.iter().filter(|n| Self::is_declaring_ident(**n)) {
+            let conflict = declaring_scope(*declaration).into_iter()
+                .flat_map(scope_idents)
+                .find(|other| other != declaration && self.node_text(*other) == new_name);
+            if let Some(other) = conflict {
+                return Err(format!("'{}' is already declared in this scope at line {}",
+                    new_name, other.start_position().row + 1));
+            }
+        }
+
+        let renamed: Vec<Point> = occurrences.iter().map(|n| n.start_position()).collect();
+        let renamed_text = |start: Point, end: Point| {
+            if renamed.contains(&start) {
+                new_name.to_string()
+            } else {
+                self.doc.get_text_range(start, end)
+            }
+        };
+        let mut changed = None;
+        walk_tree(self.syntax_tree.root_node(), |n| {
+            if changed.is_some() || n.kind() != "Ident" {
+                return;
+            }
+            let name = self.node_text(n);
+            let affected = (name == old_name && renamed.contains(&n.start_position())) || name == new_name;
+            if affected && self.binding(n) != self.binding_with(n, &renamed_text) {
+                changed = Some(n);
+            }
+        });
+        if let Some(n) = changed {
+            return Err(format!("renaming '{}' to '{}' would change what the name at line {} refers to",
+                old_name, new_name, n.start_position().row + 1));
+        }
+        Ok(occurrences.into_iter().map(node_range).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Position;
+
+    use super::super::DocHandler;
+
+    /// Rename the symbol at `line:character` and return the resulting text
+    fn rename(text: &str, line: u32, character: u32, new_name: &str) -> Result<String, String> {
+        let doc = DocHandler::from_text(text);
+        let ranges = doc.rename(Position { line, character }, new_name)?;
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        for range in ranges.iter().rev() {
+            let line = &mut lines[range.start.line as usize];
+            line.replace_range(range.start.character as usize..range.end.character as usize, new_name);
+        }
+        Ok(lines.join("\n"))
+    }
+
+    #[test]
+    fn test_rename() {
+        let text = "int a;\n\
+                    int f(int a) { int b = a; { int a = b; return a; } }\n\
+                    int main() { return a + f(a); }";
+        assert_eq!(rename(text, 1, 10, "x"),
+            Ok("int a;\nint f(int x) { int b = x; { int a = b; return a; } }\nint main() { return a + f(a); }".to_string()));
+        assert_eq!(rename(text, 2, 24, "g"),
+            Ok("int a;\nint g(int a) { int b = a; { int a = b; return a; } }\nint main() { return a + g(a); }".to_string()));
+        // The parameter would collide with a local of the function body
+        assert!(rename(text, 1, 10, "b").unwrap_err().contains("already declared"));
+        // The inner local would capture the use of `b` in its own initializer
+        assert!(rename(text, 1, 32, "b").unwrap_err().contains("would change"));
+        assert!(rename(text, 0, 4, "c").is_ok());
+        // The global would be captured by the parameter
+        assert!(rename("int a; int f(int c) { return a; }", 0, 4, "c").unwrap_err().contains("would change"));
+        assert!(rename("int main() { return x; }", 0, 20, "y").unwrap_err().contains("does not refer"));
+        assert!(rename("int main() { putint(1); return 0; }", 0, 14, "p").unwrap_err().contains("runtime"));
+    }
+}
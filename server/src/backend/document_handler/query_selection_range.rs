@@ -0,0 +1,75 @@
+use tower_lsp::lsp_types::{Position, Range, SelectionRange};
+use tree_sitter::Point;
+
+use super::{node_range, DocHandler};
+
+impl DocHandler {
+    /// The ranges of the syntax nodes enclosing `pos`, innermost first. Nodes
+    /// spanning the same text as their child, such as `ConstExp` around `Exp`,
+    /// are left out so that every step grows the selection.
+    fn enclosing_ranges(&self, pos: Position) -> Vec<Range> {
+        let point = Point {
+            row: pos.line as usize,
+            column: pos.character as usize,
+        };
+        let mut ranges: Vec<Range> = Vec::new();
+        let mut node = self.syntax_tree.root_node().descendant_for_point_range(point, point);
+        while let Some(n) = node {
+            let range = node_range(n);
+            if ranges.last() != Some(&range) {
+                ranges.push(range);
+            }
+            node = n.parent();
+        }
+        ranges
+    }
+
+    pub fn selection_ranges(&self, positions: &[Position]) -> Vec<SelectionRange> {
+        positions.iter()
+            .map(|pos| {
+                let ranges = self.enclosing_ranges(*pos);
+                let mut selection = None;
+                for range in ranges.into_iter().rev() {
+                    selection = Some(SelectionRange {
+                        range,
+                        parent: selection.map(Box::new),
+                    });
+                }
+                selection.unwrap_or(SelectionRange {
+                    range: Range { start: *pos, end: *pos },
+                    parent: None,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Position;
+
+    use super::super::DocHandler;
+
+    #[test]
+    fn test_selection_ranges() {
+        let text = "int f(int a[]) {\n    while (1) if (a[0]) a[1] = a[2 + 3] * 2;\n}";
+        let doc = DocHandler::from_text(text);
+        let lines: Vec<&str> = text.lines().collect();
+        let mut selection = doc.selection_ranges(&[Position { line: 1, character: 33 }]).pop();
+        let mut texts = Vec::new();
+        while let Some(range) = selection {
+            let (start, end) = (range.range.start, range.range.end);
+            texts.push(if start.line == end.line {
+                lines[start.line as usize][start.character as usize..end.character as usize].to_string()
+            } else {
+                format!("{}:{}-{}:{}", start.line, start.character, end.line, end.character)
+            });
+            selection = range.parent.map(|p| *p);
+        }
+        assert_eq!(texts, vec![
+            "2", "2 + 3", "a[2 + 3]", "a[2 + 3] * 2", "a[1] = a[2 + 3] * 2;",
+            "if (a[0]) a[1] = a[2 + 3] * 2;", "while (1) if (a[0]) a[1] = a[2 + 3] * 2;",
+            "0:15-2:1", "0:0-2:1",
+        ]);
+    }
+}
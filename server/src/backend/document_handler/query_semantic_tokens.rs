@@ -0,0 +1,233 @@
+use tower_lsp::lsp_types::{
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensDelta,
+    SemanticTokensEdit, SemanticTokensFullDeltaResult,
+};
+use tree_sitter::Node;
+
+use super::super::{LEGEND_MODIFIER, LEGEND_TYPE, MODIFIER_GLOBAL};
+use super::{sylib, walk_tree, DocHandler};
+
+const KEYWORDS: &[&str] = &["const", "if", "else", "while", "break", "continue", "return"];
+const OPERATORS: &[&str] = &[
+    "+", "-", "*", "/", "%", "!", "=",
+    "<", ">", "<=", ">=", "==", "!=", "&&", "||"];
+
+fn type_index(token_type: &SemanticTokenType) -> u32 {
+    LEGEND_TYPE.iter().position(|t| t == token_type).expect("Token type missing from legend") as u32
+}
+
+fn modifier_bits(modifiers: &[SemanticTokenModifier]) -> u32 {
+    modifiers.iter()
+        .map(|m| LEGEND_MODIFIER.iter().position(|l| l == m).expect("Token modifier missing from legend"))
+        .fold(0, |bits, idx| bits | (1 << idx))
+}
+
+/// A token in absolute coordinates, before delta encoding
+struct AbsoluteToken {
+    line: u32,
+    start: u32,
+    length: u32,
+    token_type: u32,
+    modifiers: u32,
+}
+
+impl DocHandler {
+    /// Semantic tokens of the whole document, delta encoded as the protocol requires
+    pub fn query_semantic_tokens(&self) -> Vec<SemanticToken> {
+        let mut tokens = Vec::new();
+        walk_tree(self.syntax_tree.root_node(), |n| {
+            if n.child_count() == 0 {
+                self.classify_leaf(n, &mut tokens);
+            }
+        });
+
+        let mut encoded = Vec::with_capacity(tokens.len());
+        let (mut last_line, mut last_start) = (0, 0);
+        for token in tokens {
+            let delta_line = token.line - last_line;
+            let delta_start = if delta_line == 0 { token.start - last_start } else { token.start };
+            encoded.push(SemanticToken {
+                delta_line,
+                delta_start,
+                length: token.length,
+                token_type: token.token_type,
+                token_modifiers_bitset: token.modifiers,
+            });
+            (last_line, last_start) = (token.line, token.start);
+        }
+        encoded
+    }
+
+    /// Compute the tokens of the document and remember them for later delta requests
+    pub fn semantic_tokens_full(&mut self) -> SemanticTokens {
+        let data = self.query_semantic_tokens();
+        self.semantic_tokens_version += 1;
+        let result_id = self.semantic_tokens_version.to_string();
+        self.semantic_tokens = Some((result_id.clone(), data.clone()));
+        SemanticTokens {
+            result_id: Some(result_id),
+            data,
+        }
+    }
+
+    /// Compute the tokens of the document as an edit of the previous result,
+    /// falling back to the full tokens when that result is not known
+    pub fn semantic_tokens_delta(&mut self, previous_result_id: &str) -> SemanticTokensFullDeltaResult {
+        let previous = match self.semantic_tokens.take() {
+            Some((result_id, data)) if result_id == previous_result_id => data,
+            _ => return SemanticTokensFullDeltaResult::Tokens(self.semantic_tokens_full()),
+        };
+        let current = self.semantic_tokens_full();
+        let prefix = previous.iter().zip(&current.data).take_while(|(a, b)| a == b).count();
+        let suffix = previous[prefix..].iter().rev().zip(current.data[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let inserted = &current.data[prefix..current.data.len() - suffix];
+        let edits = if prefix == previous.len() && prefix == current.data.len() {
+            Vec::new()
+        } else {
+            // Edits count in integers, and every token is encoded as five of them
+            vec![SemanticTokensEdit {
+                start: (prefix * 5) as u32,
+                delete_count: ((previous.len() - prefix - suffix) * 5) as u32,
+                data: Some(inserted.to_vec()),
+            }]
+        };
+        SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+            result_id: current.result_id,
+            edits,
+        })
+    }
+
+    fn classify_leaf(&self, n: Node, tokens: &mut Vec<AbsoluteToken>) {
+        let kind = n.kind();
+        let (token_type, modifiers) = if kind == "comment" {
+            // Clients may not support tokens spanning several lines
+            let text = self.node_text(n);
+            for (idx, line) in text.split('\n').enumerate() {
+                tokens.push(AbsoluteToken {
+                    line: (n.start_position().row + idx) as u32,
+                    start: if idx == 0 { n.start_position().column as u32 } else { 0 },
+                    length: line.trim_end_matches('\r').chars().count() as u32,
+                    token_type: type_index(&SemanticTokenType::COMMENT),
+                    modifiers: 0,
+                });
+            }
+            return;
+        } else if n.parent().is_some_and(|p| p.kind() == "Type") {
+            (SemanticTokenType::TYPE, Vec::new())
+        } else if KEYWORDS.contains(&kind) {
+            (SemanticTokenType::KEYWORD, Vec::new())
+        } else if OPERATORS.contains(&kind) && !n.is_named() {
+            (SemanticTokenType::OPERATOR, Vec::new())
+        } else if matches!(kind, "Decimal" | "Octal" | "Hexadecimal" | "Float") {
+            (SemanticTokenType::NUMBER, Vec::new())
+        } else if kind == "Ident" {
+            let Some(classified) = self.classify_ident(n) else {
+                return;
+            };
+            classified
+        } else {
+            return;
+        };
+        if n.start_position().row != n.end_position().row || n.start_byte() == n.end_byte() {
+            return;
+        }
+        tokens.push(AbsoluteToken {
+            line: n.start_position().row as u32,
+            start: n.start_position().column as u32,
+            length: (n.end_position().column - n.start_position().column) as u32,
+            token_type: type_index(&token_type),
+            modifiers: modifier_bits(&modifiers),
+        });
+    }
+
+    /// Classify an identifier by the kind of symbol it declares or refers to:
+    ///
+    /// - functions are `function`, with `defaultLibrary` for the runtime library
+    /// - function parameters are `parameter`
+    /// - variables and constants are `variable`, with `readonly` for constants
+    ///   and `global` for those declared at the top level
+    ///
+    /// Identifiers that declare a symbol also get `declaration`.
+    fn classify_ident(&self, ident: Node) -> Option<(SemanticTokenType, Vec<SemanticTokenModifier>)> {
+        let parent = ident.parent()?;
+        let (def, mut modifiers) = match parent.kind() {
+            "VarDef" | "ConstDef" => (parent.parent()?, vec![SemanticTokenModifier::DECLARATION]),
+            "FuncDef" | "FuncFParam" => (parent, vec![SemanticTokenModifier::DECLARATION]),
+            "Lval" | "FuncCall" => match self.resolve_ident(ident) {
+                Some(def) => (def, Vec::new()),
+                None if parent.kind() == "FuncCall" && sylib::is_runtime_function(&self.node_text(ident)) => {
+                    return Some((SemanticTokenType::FUNCTION, vec![SemanticTokenModifier::DEFAULT_LIBRARY]));
+                }
+                None => return None,
+            },
+            _ => return None,
+        };
+        let token_type = match def.kind() {
+            "FuncDef" => SemanticTokenType::FUNCTION,
+            "FuncFParam" => SemanticTokenType::PARAMETER,
+            "VarDecl" | "ConstDecl" => {
+                if def.kind() == "ConstDecl" {
+                    modifiers.push(SemanticTokenModifier::READONLY);
+                }
+                if def.parent().is_some_and(|p| p.kind() == "CompUnit") {
+                    modifiers.push(MODIFIER_GLOBAL);
+                }
+                SemanticTokenType::VARIABLE
+            }
+            _ => return None,
+        };
+        Some((token_type, modifiers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{SemanticTokenModifier, SemanticTokenType, SemanticTokensFullDeltaResult};
+
+    use super::super::super::MODIFIER_GLOBAL;
+    use super::super::DocHandler;
+    use super::{modifier_bits, type_index};
+
+    #[test]
+    fn test_semantic_tokens() {
+        let doc = DocHandler::from_text("const int n = 2;\nint f(int a) { int b = a + n; putint(b); return f(b); }");
+        let tokens: Vec<_> = doc.query_semantic_tokens().iter()
+            .map(|t| (t.delta_line, t.delta_start, t.length, t.token_type, t.token_modifiers_bitset))
+            .collect();
+        let token = |delta_start, length, token_type, modifiers: &[SemanticTokenModifier]|
+            (0, delta_start, length, type_index(&token_type), modifier_bits(modifiers));
+        assert_eq!(&tokens[..5], &[
+            token(0, 5, SemanticTokenType::KEYWORD, &[]),
+            token(6, 3, SemanticTokenType::TYPE, &[]),
+            token(4, 1, SemanticTokenType::VARIABLE,
+                &[SemanticTokenModifier::DECLARATION, SemanticTokenModifier::READONLY, MODIFIER_GLOBAL]),
+            token(2, 1, SemanticTokenType::OPERATOR, &[]),
+            token(2, 1, SemanticTokenType::NUMBER, &[]),
+        ]);
+        // int f(int a) declares a function and a parameter
+        assert!(tokens.contains(&token(4, 1, SemanticTokenType::FUNCTION, &[SemanticTokenModifier::DECLARATION])));
+        assert!(tokens.contains(&token(4, 1, SemanticTokenType::PARAMETER, &[SemanticTokenModifier::DECLARATION])));
+        // int b declares a local variable
+        assert!(tokens.contains(&token(4, 1, SemanticTokenType::VARIABLE, &[SemanticTokenModifier::DECLARATION])));
+        // a + n
+        assert!(tokens.contains(&token(2, 1, SemanticTokenType::PARAMETER, &[])));
+        assert!(tokens.contains(&token(2, 1, SemanticTokenType::VARIABLE,
+            &[SemanticTokenModifier::READONLY, MODIFIER_GLOBAL])));
+        // putint, then the recursive call to f
+        assert!(tokens.contains(&token(3, 6, SemanticTokenType::FUNCTION, &[SemanticTokenModifier::DEFAULT_LIBRARY])));
+        assert!(tokens.contains(&token(7, 1, SemanticTokenType::FUNCTION, &[])));
+    }
+
+    #[test]
+    fn test_semantic_tokens_delta() {
+        let mut doc = DocHandler::from_text("int a;\nint b;");
+        let first = doc.semantic_tokens_full();
+        match doc.semantic_tokens_delta(first.result_id.as_deref().unwrap()) {
+            SemanticTokensFullDeltaResult::TokensDelta(delta) => assert!(delta.edits.is_empty()),
+            _ => panic!("expected a delta"),
+        }
+        assert!(matches!(doc.semantic_tokens_delta("stale"), SemanticTokensFullDeltaResult::Tokens(_)));
+    }
+}
@@ -0,0 +1,145 @@
+use tower_lsp::lsp_types::{
+    Documentation, ParameterInformation, ParameterLabel, Position, SignatureHelp, SignatureInformation,
+};
+use tree_sitter::{Node, Point};
+
+use super::query_references::Binding;
+use super::{sylib, walk_tree, DocHandler};
+
+/// A signature as its parameter labels, ready to be laid out
+struct Signature {
+    ret: String,
+    name: String,
+    params: Vec<String>,
+    documentation: Option<String>,
+}
+
+impl Signature {
+    fn into_information(self, active_parameter: u32) -> SignatureInformation {
+        let mut label = format!("{} {}(", self.ret, self.name);
+        let mut parameters = Vec::new();
+        for (idx, param) in self.params.into_iter().enumerate() {
+            if idx > 0 {
+                label.push_str(", ");
+            }
+            let start = label.encode_utf16().count() as u32;
+            label.push_str(&param);
+            let end = label.encode_utf16().count() as u32;
+            parameters.push(ParameterInformation {
+                label: ParameterLabel::LabelOffsets([start, end]),
+                documentation: None,
+            });
+        }
+        label.push(')');
+        SignatureInformation {
+            label,
+            documentation: self.documentation.map(Documentation::String),
+            parameters: Some(parameters),
+            active_parameter: Some(active_parameter),
+        }
+    }
+}
+
+impl DocHandler {
+    /// The identifier of the function whose argument list encloses `pos`,
+    /// with the index of the argument the cursor is in. This scans the tokens
+    /// before the cursor rather than looking for a `FuncCall`, since calls
+    /// still being typed are usually broken up into `ERROR` nodes.
+    fn enclosing_call(&self, pos: Position) -> Option<(Node<'_>, u32)> {
+        let point = Point {
+            row: pos.line as usize,
+            column: pos.character as usize,
+        };
+        let mut tokens = Vec::new();
+        walk_tree(self.syntax_tree.root_node(), |n| {
+            if n.child_count() == 0 && !n.is_extra() && n.end_position() <= point && !n.is_missing() {
+                tokens.push(n);
+            }
+        });
+
+        let mut depth = 0;
+        let mut commas = 0;
+        for (idx, token) in tokens.iter().enumerate().rev() {
+            match token.kind() {
+                ")" => depth += 1,
+                "(" if depth > 0 => depth -= 1,
+                "(" => match idx.checked_sub(1).map(|prev| tokens[prev]) {
+                    Some(callee) if callee.kind() == "Ident" => return Some((callee, commas)),
+                    // A parenthesized expression inside an argument
+                    _ => commas = 0,
+                },
+                "," if depth == 0 => commas += 1,
+                ";" | "{" | "}" if depth == 0 => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// The signature of the function named by `ident`, which may be defined
+    /// later in the document or come from the runtime library
+    fn callee_signature(&self, ident: Node) -> Option<Signature> {
+        let name = self.node_text(ident);
+        let func = match self.binding(ident) {
+            Some(Binding::Declared(decl)) => Some(decl.parent()?).filter(|f| f.kind() == "FuncDef"),
+            _ => self.top_level_functions().into_iter()
+                .find(|f| f.child_by_field_name("ident").is_some_and(|i| self.node_text(i) == name)),
+        };
+        if let Some(func) = func {
+            let mut cursor = func.walk();
+            let params = func.children_by_field_name("params", &mut cursor)
+                .map(|p| self.node_text(p).split_whitespace().collect::<Vec<_>>().join(" "))
+                .collect();
+            return Some(Signature {
+                ret: self.node_text(func.child_by_field_name("type")?),
+                name,
+                params,
+                documentation: None,
+            });
+        }
+        let func = sylib::runtime_function(&name)?;
+        Some(Signature {
+            ret: func.return_type.to_string(),
+            name,
+            params: func.param_labels(),
+            documentation: func.doc,
+        })
+    }
+
+    pub fn signature_help(&self, pos: Position) -> Option<SignatureHelp> {
+        let (callee, active_parameter) = self.enclosing_call(pos)?;
+        let signature = self.callee_signature(callee)?;
+        Some(SignatureHelp {
+            signatures: vec![signature.into_information(active_parameter)],
+            active_signature: Some(0),
+            active_parameter: Some(active_parameter),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Position;
+
+    use super::super::DocHandler;
+
+    /// The label and active parameter of the signature help at the end of `text`
+    fn signature_help(text: &str) -> Option<(String, u32)> {
+        let doc = DocHandler::from_text(text);
+        let line = text.lines().count() as u32 - 1;
+        let character = text.lines().last().unwrap().len() as u32;
+        let help = doc.signature_help(Position { line, character })?;
+        Some((help.signatures[0].label.clone(), help.active_parameter.unwrap()))
+    }
+
+    #[test]
+    fn test_signature_help() {
+        let f = "int f(int a, int b[][3]) { return 0; }\nint main() {\n";
+        assert_eq!(signature_help(&format!("{}  f(", f)), Some(("int f(int a, int b[][3])".to_string(), 0)));
+        assert_eq!(signature_help(&format!("{}  f(1, (2 + 3)", f)), Some(("int f(int a, int b[][3])".to_string(), 1)));
+        assert_eq!(signature_help(&format!("{}  putarray(3, f(g(1), 2)", f)), Some(("void putarray(int n, int a[])".to_string(), 1)));
+        assert_eq!(signature_help(&format!("{}  putarray(3, f(g(1), ", f)), Some(("int f(int a, int b[][3])".to_string(), 1)));
+        assert_eq!(signature_help(&format!("{}  putarray(3, a);", f)), None);
+        assert_eq!(signature_help(&format!("{}  if (1", f)), None);
+    }
+}
@@ -1,4 +1,3 @@
-use std::path::Display;
 use std::collections::BTreeSet;
 use tower_lsp::lsp_types::Position;
 
@@ -8,6 +7,11 @@ use super::DocHandler;
 pub enum SymbolKind {
     Variable,
     Function,
+    /// A `const` scalar or array
+    Constant,
+    Parameter,
+    /// A variable of array type
+    Array,
 }
 
 impl std::fmt::Display for SymbolKind {
@@ -15,11 +19,88 @@ impl std::fmt::Display for SymbolKind {
         match self {
             SymbolKind::Variable => write!(f, "variable"),
             SymbolKind::Function => write!(f, "function"),
+            SymbolKind::Constant => write!(f, "constant"),
+            SymbolKind::Parameter => write!(f, "parameter"),
+            SymbolKind::Array => write!(f, "array"),
         }
     }
 }
 
+impl SymbolKind {
+    /// The kind of the symbol introduced by a declaring `Ident`
+    pub(super) fn of_declaration(ident: tree_sitter::Node) -> Option<SymbolKind> {
+        let parent = ident.parent()?;
+        match parent.kind() {
+            "FuncDef" => Some(SymbolKind::Function),
+            "FuncFParam" => Some(SymbolKind::Parameter),
+            "ConstDef" => Some(SymbolKind::Constant),
+            "VarDef" if parent.child_by_field_name("array_qualifier").is_some() => Some(SymbolKind::Array),
+            "VarDef" => Some(SymbolKind::Variable),
+            _ => None,
+        }
+    }
 
+    pub(super) fn to_lsp(&self) -> tower_lsp::lsp_types::SymbolKind {
+        match self {
+            SymbolKind::Variable | SymbolKind::Parameter => tower_lsp::lsp_types::SymbolKind::VARIABLE,
+            SymbolKind::Function => tower_lsp::lsp_types::SymbolKind::FUNCTION,
+            SymbolKind::Constant => tower_lsp::lsp_types::SymbolKind::CONSTANT,
+            SymbolKind::Array => tower_lsp::lsp_types::SymbolKind::ARRAY,
+        }
+    }
+}
+
+/// The `Ident` nodes introduced by a `VarDecl`, `ConstDecl`, `FuncDef` or `FuncFParam`
+pub(super) fn declared_idents(n: tree_sitter::Node) -> Vec<tree_sitter::Node> {
+    match n.kind() {
+        "VarDecl" | "ConstDecl" => {
+            let mut cursor = n.walk();
+            let defs = n.children_by_field_name("defs", &mut cursor);
+            defs.filter_map(|def| def.child_by_field_name("ident")).collect()
+        }
+        "FuncDef" | "FuncFParam" => n.child_by_field_name("ident").into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Identifiers declared in the scope opened by `scope`. The global scope
+/// holds variables, constants and functions alike, while parameters share a
+/// scope with the outermost block of the function body.
+pub(super) fn scope_idents(scope: tree_sitter::Node) -> Vec<tree_sitter::Node> {
+    let mut cursor = scope.walk();
+    let items: Vec<_> = scope.named_children(&mut cursor).collect();
+    match scope.kind() {
+        "FuncDef" => items.into_iter()
+            .flat_map(|item| match item.kind() {
+                "FuncFParam" => declared_idents(item),
+                "Block" => scope_idents(item),
+                _ => Vec::new(),
+            })
+            .collect(),
+        _ => items.into_iter().flat_map(declared_idents).collect(),
+    }
+}
+
+/// The node opening the scope that a declaring `Ident` belongs to, in the
+/// sense of `scope_idents`
+pub(super) fn declaring_scope(ident: tree_sitter::Node) -> Option<tree_sitter::Node> {
+    let parent = ident.parent()?;
+    match parent.kind() {
+        "FuncDef" | "FuncFParam" => parent.parent(),
+        "VarDef" | "ConstDef" => {
+            let scope = parent.parent()?.parent()?;
+            match scope.parent() {
+                Some(func) if scope.kind() == "Block" && func.kind() == "FuncDef" => Some(func),
+                _ => Some(scope),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The symbols visible at `n`, innermost first. Like `find_definition`, this
+/// walks back through preceding siblings and ancestors, so the locals of
+/// blocks nested in earlier statements are not visible.
 fn query_symbols(mut n: tree_sitter::Node, get_text_range: &impl Fn(tree_sitter::Point, tree_sitter::Point) -> String) -> Vec<Symbol>
 {
     let mut symbols = Vec::new();
@@ -28,9 +109,8 @@ fn query_symbols(mut n: tree_sitter::Node, get_text_range: &impl Fn(tree_sitter:
     {
         FromSibling,
         FromChild,
-        NoJump,
     }
-    let mut last_jump = LastJump::NoJump;
+    let mut last_jump;
     loop {
         if n.prev_named_sibling().is_some() {
             n = n.prev_named_sibling().unwrap();
@@ -43,17 +123,13 @@ fn query_symbols(mut n: tree_sitter::Node, get_text_range: &impl Fn(tree_sitter:
         }
         if n.kind() == "VarDecl" || n.kind() == "ConstDecl" {
             // Check variable/constant definitions
-            let mut cursor = n.walk();
-            let defs = n.children_by_field_name("defs", &mut cursor);
-            for def in defs {
-                if let Some(def_ident) = def.child_by_field_name("ident") {
-                    let def_name = get_text_range(
-                        def_ident.start_position(), def_ident.end_position());
-                    symbols.push(Symbol {
-                        name: def_name,
-                        kind: SymbolKind::Variable,
-                    });
-                }
+            for def_ident in declared_idents(n) {
+                let def_name = get_text_range(
+                    def_ident.start_position(), def_ident.end_position());
+                symbols.push(Symbol {
+                    name: def_name,
+                    kind: SymbolKind::of_declaration(def_ident).unwrap_or(SymbolKind::Variable),
+                });
             }
         }
         else if n.kind() == "FuncDef"
@@ -83,7 +159,7 @@ fn query_symbols(mut n: tree_sitter::Node, get_text_range: &impl Fn(tree_sitter:
                 param_ident.start_position(), param_ident.end_position());
                 symbols.push(Symbol {
                     name: param_name,
-                    kind: SymbolKind::Variable,
+                    kind: SymbolKind::Parameter,
                 });
             }
             }
@@ -111,6 +187,16 @@ pub struct Symbol {
 }
 
 impl DocHandler {
+    /// Every `FuncDef` at the top level of the document
+    pub(super) fn top_level_functions(&self) -> Vec<tree_sitter::Node<'_>> {
+        let Some(comp_unit) = self.syntax_tree.root_node().named_child(0) else {
+            return Vec::new();
+        };
+        let mut cursor = comp_unit.walk();
+        let funcs = comp_unit.named_children(&mut cursor).filter(|n| n.kind() == "FuncDef").collect();
+        funcs
+    }
+
     pub fn query_symbols(&self, position: Position) -> Vec<Symbol> {
         let node = self.syntax_tree.root_node().descendant_for_point_range(
             tree_sitter::Point {
@@ -129,4 +215,27 @@ impl DocHandler {
             Vec::new()
         }
     }   
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Position;
+
+    use super::super::DocHandler;
+
+    #[test]
+    fn test_query_symbols() {
+        let doc = DocHandler::from_text("int g;\n\
+                                         int f(int p) {\n\
+                                         \x20   if (p) { int a; } else { int b; }\n\
+                                         \x20   while (p) { int c;\n\
+                                         \x20       p = c;\n\
+                                         \x20   }\n\
+                                         }");
+        let names = |line, character| -> Vec<String> {
+            doc.query_symbols(Position { line, character }).into_iter().map(|s| s.name).collect()
+        };
+        assert_eq!(names(4, 12), vec!["c", "f", "p", "g"]);
+        assert_eq!(names(2, 31), vec!["b", "f", "p", "g"]);
+    }
+}
@@ -0,0 +1,348 @@
+//! The SysY runtime library, whose functions every program may call without
+//! declaring them. The built-in set can be replaced by the functions of a
+//! user's own `sylib.h`.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, RwLock};
+
+use tower_lsp::lsp_types::{Position, Range};
+
+use super::types::{BaseType, ValueType};
+
+/// A function provided by the SysY runtime library, callable without a declaration
+#[derive(Debug, Clone)]
+pub struct RuntimeFunction {
+    pub name: String,
+    pub return_type: BaseType,
+    pub params: Vec<(String, ValueType)>,
+    pub variadic: bool,
+    pub doc: Option<String>,
+    /// The range of the name in the `sylib.h` declaring the function, which
+    /// built-in functions do not have
+    pub header_range: Option<Range>,
+}
+
+impl RuntimeFunction {
+    /// The parameters as they would be declared, such as `int a[]`
+    pub fn param_labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self.params.iter()
+            .map(|(name, ty)| {
+                let dims: String = ty.dims.iter()
+                    .map(|dim| dim.map_or("[]".to_string(), |size| format!("[{}]", size)))
+                    .collect();
+                format!("{} {}{}", ty.base, name, dims)
+            })
+            .collect();
+        if self.variadic {
+            labels.push("...".to_string());
+        }
+        labels
+    }
+
+    /// A signature such as `void (int, int[])`
+    pub fn signature(&self) -> String {
+        let mut params: Vec<String> = self.params.iter().map(|(_, ty)| ty.to_string()).collect();
+        if self.variadic {
+            params.push("...".to_string());
+        }
+        format!("{} ({})", self.return_type, params.join(", "))
+    }
+
+    /// The declaration of the function, such as `void putarray(int n, int a[])`
+    pub fn declaration(&self) -> String {
+        format!("{} {}({})", self.return_type, self.name, self.param_labels().join(", "))
+    }
+}
+
+fn runtime_function_of(name: &str, return_type: BaseType, params: &[(&str, BaseType, usize)], variadic: bool, doc: &str)
+-> RuntimeFunction {
+    RuntimeFunction {
+        name: name.to_string(),
+        return_type,
+        params: params.iter()
+            .map(|&(param, base, rank)| (param.to_string(), ValueType {
+                base,
+                dims: vec![None; rank],
+            }))
+            .collect(),
+        variadic,
+        doc: Some(doc.to_string()),
+        header_range: None,
+    }
+}
+
+fn builtin_functions() -> Vec<RuntimeFunction> {
+    use BaseType::{Float, Int, Void};
+    vec![
+        runtime_function_of("getint", Int, &[], false,
+            "Read an integer from standard input and return it."),
+        runtime_function_of("getch", Int, &[], false,
+            "Read a character from standard input and return its ASCII code."),
+        runtime_function_of("getfloat", Float, &[], false,
+            "Read a decimal or hexadecimal floating-point number from standard input and return it."),
+        runtime_function_of("getarray", Int, &[("a", Int, 1)], false,
+            "Read a count `n` from standard input, then `n` integers into `a`. Returns `n`."),
+        runtime_function_of("getfarray", Int, &[("a", Float, 1)], false,
+            "Read a count `n` from standard input, then `n` floating-point numbers into `a`. Returns `n`."),
+        runtime_function_of("putint", Void, &[("a", Int, 0)], false,
+            "Print the integer `a` to standard output."),
+        runtime_function_of("putch", Void, &[("a", Int, 0)], false,
+            "Print the character with ASCII code `a` to standard output."),
+        runtime_function_of("putfloat", Void, &[("a", Float, 0)], false,
+            "Print the floating-point number `a` to standard output in the hexadecimal `%a` format."),
+        runtime_function_of("putarray", Void, &[("n", Int, 0), ("a", Int, 1)], false,
+            "Print `n`, a colon and the first `n` elements of `a` separated by spaces, then a newline."),
+        runtime_function_of("putfarray", Void, &[("n", Int, 0), ("a", Float, 1)], false,
+            "Print `n`, a colon and the first `n` elements of `a` in the `%a` format, then a newline."),
+        // The format string of `putf` cannot be written in SysY itself
+        runtime_function_of("putf", Void, &[], true,
+            "Print a string literal with `printf`-style formatting of the remaining arguments."),
+        runtime_function_of("starttime", Void, &[], false,
+            "Start a timer. The time until the next `stoptime()` is reported when the program exits."),
+        runtime_function_of("stoptime", Void, &[], false,
+            "Stop the timer started by the last `starttime()`."),
+    ]
+}
+
+static RUNTIME_FUNCTIONS: LazyLock<RwLock<Arc<Vec<RuntimeFunction>>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(builtin_functions())));
+
+/// The `sylib.h` the runtime library was loaded from, if any
+static RUNTIME_HEADER: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Every function of the runtime library in use
+pub fn runtime_functions() -> Arc<Vec<RuntimeFunction>> {
+    RUNTIME_FUNCTIONS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub fn runtime_function(name: &str) -> Option<RuntimeFunction> {
+    runtime_functions().iter().find(|f| f.name == name).cloned()
+}
+
+pub fn is_runtime_function(name: &str) -> bool {
+    runtime_functions().iter().any(|f| f.name == name)
+}
+
+/// The `sylib.h` declaring the runtime library in use, which is `None` for
+/// the built-in one
+pub fn runtime_header() -> Option<PathBuf> {
+    RUNTIME_HEADER.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Use the functions declared in the header at `path` as the runtime library,
+/// or the built-in set when `path` is `None`. Returns how many were loaded.
+pub fn load_runtime_library(path: Option<&Path>) -> Result<usize, String> {
+    let functions = match path {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| format!("cannot read '{}': {}", path.display(), e))?;
+            parse_header(&text)
+        }
+        None => builtin_functions(),
+    };
+    let count = functions.len();
+    *RUNTIME_FUNCTIONS.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(functions);
+    *RUNTIME_HEADER.write().unwrap_or_else(|e| e.into_inner()) = path.map(Path::to_path_buf);
+    Ok(count)
+}
+
+fn parse_base_type(word: &str) -> Option<BaseType> {
+    match word {
+        "int" => Some(BaseType::Int),
+        "float" => Some(BaseType::Float),
+        "void" => Some(BaseType::Void),
+        _ => None,
+    }
+}
+
+/// Remove `/* */` and `//` comments from C source, leaving line breaks
+fn strip_comments(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("/*").into_iter().chain(rest.find("//")).min() {
+        result.push_str(&rest[..start]);
+        let end = if rest[start..].starts_with("/*") {
+            rest[start..].find("*/").map(|e| start + e + 2)
+        } else {
+            rest[start..].find('\n').map(|e| start + e)
+        };
+        let comment = end.map_or(&rest[start..], |end| &rest[start..end]);
+        // Keep the lines of block comments so that positions stay the same
+        result.extend(comment.chars().filter(|c| *c == '\n'));
+        rest = end.map_or("", |end| &rest[end..]);
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Parse a parameter such as `int a[][4]`. Returns `None` for types that
+/// SysY cannot express, like the `char a[]` format string of `putf`.
+fn parse_param(param: &str) -> Option<(String, ValueType)> {
+    let (base, declarator) = param.trim().split_once(char::is_whitespace)?;
+    let base = parse_base_type(base).filter(|b| *b != BaseType::Void)?;
+    let declarator = declarator.trim();
+    let (name, dims) = match declarator.split_once('[') {
+        Some((name, dims)) => (name, dims.split('[')
+            .map(|dim| dim.trim_end_matches(|c: char| c == ']' || c.is_whitespace()).parse().ok())
+            .collect()),
+        None => (declarator, Vec::new()),
+    };
+    Some((name.trim().to_string(), ValueType { base, dims }))
+}
+
+/// The functions declared by a C header such as `sylib.h`. Zero-argument
+/// function-like macros, which is how `starttime()` and `stoptime()` are
+/// provided, count as functions returning `void`. Names starting with an
+/// underscore are internal to the library and left out.
+pub fn parse_header(text: &str) -> Vec<RuntimeFunction> {
+    let builtins = builtin_functions();
+    let doc_of = |name: &str| builtins.iter().find(|f| f.name == name).and_then(|f| f.doc.clone());
+    let mut functions = Vec::new();
+    let mut declarations = String::new();
+    let text = strip_comments(text);
+    for line in text.lines() {
+        let Some(directive) = line.trim().strip_prefix('#') else {
+            declarations.push_str(line);
+            declarations.push('\n');
+            continue;
+        };
+        let Some(definition) = directive.trim().strip_prefix("define") else {
+            continue;
+        };
+        if let Some((name, _)) = definition.trim().split_once("()") {
+            if !name.starts_with('_') && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                functions.push(RuntimeFunction {
+                    name: name.to_string(),
+                    return_type: BaseType::Void,
+                    params: Vec::new(),
+                    variadic: false,
+                    doc: doc_of(name),
+                    header_range: None,
+                });
+            }
+        }
+    }
+
+    for statement in declarations.split(';') {
+        let statement = statement.trim();
+        if statement.contains(['{', '}', '*']) {
+            continue;
+        }
+        let Some((return_type, declarators)) = statement.split_once(char::is_whitespace) else {
+            continue;
+        };
+        let Some(return_type) = parse_base_type(return_type) else {
+            continue;
+        };
+        // Split declarators at the commas outside of parameter lists
+        let mut depth = 0;
+        let mut pieces = vec![String::new()];
+        for c in declarators.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    pieces.push(String::new());
+                    continue;
+                }
+                _ => {}
+            }
+            pieces.last_mut().unwrap().push(c);
+        }
+        for declarator in pieces {
+            let Some((name, params)) = declarator.trim().strip_suffix(')').and_then(|d| d.split_once('(')) else {
+                continue;
+            };
+            let name = name.trim();
+            if name.is_empty() || name.starts_with('_') {
+                continue;
+            }
+            let mut function = RuntimeFunction {
+                name: name.to_string(),
+                return_type,
+                params: Vec::new(),
+                variadic: false,
+                doc: doc_of(name),
+                header_range: None,
+            };
+            let params = params.trim();
+            if !params.is_empty() && params != "void" {
+                for param in params.split(',') {
+                    // Parameters from the first unsupported one on can only be variadic
+                    match parse_param(param) {
+                        Some(param) if !function.variadic => function.params.push(param),
+                        _ => function.variadic = true,
+                    }
+                }
+            }
+            functions.push(function);
+        }
+    }
+    for function in &mut functions {
+        function.header_range = name_range(&text, &function.name);
+    }
+    functions
+}
+
+/// The range of the first `name` in `text` that is followed by a `(`
+fn name_range(text: &str, name: &str) -> Option<Range> {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    text.lines().enumerate().find_map(|(line, content)| {
+        content.match_indices(name)
+            .find(|(start, _)| {
+                let before = content[..*start].chars().next_back();
+                let after = content[start + name.len()..].trim_start();
+                !before.is_some_and(is_ident) && after.starts_with('(')
+            })
+            .map(|(start, _)| {
+                let character = content[..start].chars().count() as u32;
+                Range {
+                    start: Position { line: line as u32, character },
+                    end: Position { line: line as u32, character: character + name.chars().count() as u32 },
+                }
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{builtin_functions, parse_header, RuntimeFunction};
+
+    #[test]
+    fn test_parse_header() {
+        let header = "#ifndef __SYLIB_H_\n\
+                      #include<stdio.h>\n\
+                      /* Input & output functions */\n\
+                      int getint(),getch(),getarray(int a[]);\n\
+                      float getfloat();\n\
+                      int getfarray(float a[]);\n\
+                      void putint(int a),putch(int a),putarray(int n,int a[]);\n\
+                      void putfloat(float a);\n\
+                      void putfarray(int n, float a[]);\n\
+                      void putf(char a[], ...);\n\
+                      void putmatrix(int n, int m[][16]); // a custom helper\n\
+                      #define starttime() _sysy_starttime(__LINE__)\n\
+                      struct timeval _sysy_start,_sysy_end;\n\
+                      int _sysy_idx;\n\
+                      __attribute((constructor)) void before_main();\n\
+                      void _sysy_starttime(int lineno);\n\
+                      #endif";
+        let functions: Vec<_> = parse_header(header).iter().map(|f| f.declaration()).collect();
+        assert_eq!(functions, vec![
+            "void starttime()", "int getint()", "int getch()", "int getarray(int a[])",
+            "float getfloat()", "int getfarray(float a[])",
+            "void putint(int a)", "void putch(int a)", "void putarray(int n, int a[])",
+            "void putfloat(float a)", "void putfarray(int n, float a[])",
+            "void putf(...)", "void putmatrix(int n, int m[][16])",
+        ]);
+        let functions = parse_header(header);
+        assert!(functions[1].doc.is_some());
+        assert!(functions[12].doc.is_none());
+        let position = |f: &RuntimeFunction| f.header_range.map(|r| (r.start.line, r.start.character, r.end.character));
+        // `starttime`, `getch` and `putf`, which follow a block comment
+        assert_eq!(position(&functions[0]), Some((11, 8, 17)));
+        assert_eq!(position(&functions[2]), Some((3, 13, 18)));
+        assert_eq!(position(&functions[11]), Some((9, 5, 9)));
+        assert!(builtin_functions().iter().all(|f| f.header_range.is_none()));
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt;
+
+use tree_sitter::Node;
+
+use super::const_eval::ConstValue;
+use super::{ast, sylib, DocHandler};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseType {
+    Int,
+    Float,
+    Void,
+}
+
+impl fmt::Display for BaseType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BaseType::Int => write!(f, "int"),
+            BaseType::Float => write!(f, "float"),
+            BaseType::Void => write!(f, "void"),
+        }
+    }
+}
+
+/// The type of a value: a scalar when `dims` is empty, an array otherwise.
+/// A dimension is `None` when it is omitted, as for the first dimension of
+/// array parameters, or when its size is not known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueType {
+    pub base: BaseType,
+    pub dims: Vec<Option<i64>>,
+}
+
+impl ValueType {
+    pub fn scalar(base: BaseType) -> Self {
+        ValueType { base, dims: Vec::new() }
+    }
+
+    pub fn is_array(&self) -> bool {
+        !self.dims.is_empty()
+    }
+
+    /// Whether this is an `int` or `float` scalar, which convert to each other implicitly
+    pub fn is_arithmetic(&self) -> bool {
+        !self.is_array() && matches!(self.base, BaseType::Int | BaseType::Float)
+    }
+
+    /// The type left after applying `count` subscripts
+    pub fn index(&self, count: usize) -> Option<ValueType> {
+        Some(ValueType {
+            base: self.base,
+            dims: self.dims.get(count..)?.to_vec(),
+        })
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.base)?;
+        for dim in &self.dims {
+            match dim {
+                Some(size) => write!(f, "[{}]", size)?,
+                None => write!(f, "[]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolType {
+    Variable {
+        ty: ValueType,
+        constant: bool,
+    },
+    Function {
+        ret: BaseType,
+        params: Vec<ValueType>,
+        variadic: bool,
+    },
+}
+
+impl DocHandler {
+    pub(super) fn base_type(&self, type_node: Node) -> Option<BaseType> {
+        match self.node_text(type_node).as_str() {
+            "int" => Some(BaseType::Int),
+            "float" => Some(BaseType::Float),
+            "void" => Some(BaseType::Void),
+            _ => None,
+        }
+    }
+
+    /// The size of an array dimension, if it is a valid constant expression
+    pub(super) fn dim_value(&self, exp: Node) -> Option<i64> {
+        self.eval_const(exp).ok().and_then(ConstValue::as_int).map(i64::from)
+    }
+
+    fn qualifier_dims(&self, qualifier: Option<Node>) -> Vec<Option<i64>> {
+        qualifier
+            .map(|q| ast::array_dims(q).into_iter().map(|d| self.dim_value(d)).collect())
+            .unwrap_or_default()
+    }
+
+    /// The type of a `VarDef` or `ConstDef`
+    pub(super) fn def_type(&self, def: Node) -> Option<ValueType> {
+        let decl = def.parent()?;
+        let base = self.base_type(decl.child_by_field_name("type")?)?;
+        Some(ValueType {
+            base,
+            dims: self.qualifier_dims(def.child_by_field_name("array_qualifier")),
+        })
+    }
+
+    /// The type of a `FuncFParam`, whose first array dimension is always omitted
+    pub(super) fn param_type(&self, param: Node) -> Option<ValueType> {
+        let base = self.base_type(param.child_by_field_name("type")?)?;
+        let dims = match param.child_by_field_name("array_qualifier") {
+            Some(qualifier) => {
+                let mut dims = vec![None];
+                dims.extend(self.qualifier_dims(Some(qualifier)));
+                dims
+            }
+            None => Vec::new(),
+        };
+        Some(ValueType { base, dims })
+    }
+
+    /// The type of the symbol `name` declared by a definition node
+    pub(super) fn symbol_type(&self, def: Node, name: &str) -> Option<SymbolType> {
+        match def.kind() {
+            "VarDecl" | "ConstDecl" => self.symbol_type(self.declarator(def, name)?, name),
+            "VarDef" | "ConstDef" => Some(SymbolType::Variable {
+                ty: self.def_type(def)?,
+                constant: def.kind() == "ConstDef",
+            }),
+            "FuncFParam" => Some(SymbolType::Variable {
+                ty: self.param_type(def)?,
+                constant: false,
+            }),
+            "FuncDef" => {
+                let mut cursor = def.walk();
+                let params = def.children_by_field_name("params", &mut cursor)
+                    .map(|p| self.param_type(p))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(SymbolType::Function {
+                    ret: self.base_type(def.child_by_field_name("type")?)?,
+                    params,
+                    variadic: false,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The type of the symbol an `Ident` refers to. Calls that do not resolve
+    /// to a declaration fall back to the runtime library.
+    pub(super) fn ident_type(&self, ident: Node) -> Option<SymbolType> {
+        let name = self.node_text(ident);
+        if let Some(def) = self.resolve_ident(ident) {
+            return self.symbol_type(def, &name);
+        }
+        let func = sylib::runtime_function(&name)?;
+        Some(SymbolType::Function {
+            ret: func.return_type,
+            params: func.params.iter().map(|(_, ty)| ty.clone()).collect(),
+            variadic: func.variadic,
+        })
+    }
+}
@@ -13,20 +13,26 @@ impl Backend {
         let doc_handler = DocHandler::new(text, parser_ref);
         self.documents.insert(params.text_document.uri.clone(), 
             Mutex::new(doc_handler));
+        drop(parser);
+        self.update_diagnostics(params.text_document.uri, Some(params.text_document.version)).await;
     }
 
     pub async fn did_change_handler(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri.clone();
-        let handler = self.documents.get_mut(&uri).expect("Document not found");
-        let mut handler = handler.lock().await;
-        let mut parser = self.parser.lock().await;
-        for change in params.content_changes {
-            handler.incremental_update(&change, parser.deref_mut()).await;
+        {
+            let handler = self.documents.get_mut(&uri).expect("Document not found");
+            let mut handler = handler.lock().await;
+            let mut parser = self.parser.lock().await;
+            for change in params.content_changes {
+                handler.incremental_update(&change, parser.deref_mut()).await;
+            }
         }
+        self.update_diagnostics(uri, Some(params.text_document.version)).await;
     }
 
     pub async fn did_close_handler(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri;
         self.documents.remove(&uri);
+        self.clear_diagnostics(uri).await;
     }
 }
\ No newline at end of file
@@ -0,0 +1,13 @@
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeParams};
+
+use super::Backend;
+
+impl Backend {
+    pub async fn folding_range_handler(&self, params: FoldingRangeParams)
+    -> Result<Option<Vec<FoldingRange>>, tower_lsp::jsonrpc::Error> {
+        let doc_handler = self.documents.get(&params.text_document.uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let doc = doc_handler.lock().await;
+        Ok(Some(doc.folding_ranges()))
+    }
+}
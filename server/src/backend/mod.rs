@@ -2,14 +2,25 @@ use tokio::sync::Mutex;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{LanguageServer, Client};
 use dashmap::DashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 mod document_handler;
 mod file_reqs;
 mod definition_reqs;
 mod completion_reqs;
+mod diagnostic_reqs;
+mod semantic_tokens_reqs;
+mod references_reqs;
+mod rename_reqs;
+mod symbol_reqs;
+mod workspace_reqs;
+mod call_hierarchy_reqs;
+mod selection_range_reqs;
+mod folding_range_reqs;
+mod signature_help_reqs;
+mod sylib_reqs;
 
-#[allow(dead_code)]
 const LEGEND_TYPE: &[SemanticTokenType] = &[
     SemanticTokenType::KEYWORD,
     SemanticTokenType::FUNCTION,
@@ -18,12 +29,26 @@ const LEGEND_TYPE: &[SemanticTokenType] = &[
     SemanticTokenType::COMMENT,
     SemanticTokenType::OPERATOR,
     SemanticTokenType::TYPE,
+    SemanticTokenType::PARAMETER,
+];
+
+/// Marks variables declared at the top level, which have no standard modifier
+const MODIFIER_GLOBAL: SemanticTokenModifier = SemanticTokenModifier::new("global");
+
+const LEGEND_MODIFIER: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
+    MODIFIER_GLOBAL,
 ];
 
 pub struct Backend {
     pub client: Client,
     documents: Arc<DashMap<Url, Mutex<document_handler::DocHandler>>>,
     parser: Arc<Mutex<tree_sitter::Parser>>,
+    /// Top-level symbols of every `.sy` file in the workspace
+    workspace_index: Arc<DashMap<Url, Vec<SymbolInformation>>>,
+    workspace_folders: Arc<Mutex<Vec<PathBuf>>>,
 }
 
 impl Backend {
@@ -35,28 +60,64 @@ impl Backend {
             client,
             documents: Arc::new(DashMap::new()),
             parser: Arc::new(Mutex::new(parser)),
+            workspace_index: Arc::new(DashMap::new()),
+            workspace_folders: Arc::new(Mutex::new(Vec::new())),
         }
     }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _params: InitializeParams) -> Result<InitializeResult, tower_lsp::jsonrpc::Error> {
-        let mut capabilities = ServerCapabilities::default();
-
-        capabilities.text_document_sync = Some(TextDocumentSyncCapability::Options(
-            TextDocumentSyncOptions {
-                open_close: Some(true),
-                change: Some(TextDocumentSyncKind::INCREMENTAL),
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult, tower_lsp::jsonrpc::Error> {
+        self.set_workspace_folders(&params).await;
+        self.init_runtime_library(&params).await;
+        let capabilities = ServerCapabilities {
+            text_document_sync: Some(TextDocumentSyncCapability::Options(
+                TextDocumentSyncOptions {
+                    open_close: Some(true),
+                    change: Some(TextDocumentSyncKind::INCREMENTAL),
+                    ..Default::default()
+                }
+            )),
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
+            definition_provider: Some(OneOf::Left(true)),
+            references_provider: Some(OneOf::Left(true)),
+            document_highlight_provider: Some(OneOf::Left(true)),
+            document_symbol_provider: Some(OneOf::Left(true)),
+            workspace_symbol_provider: Some(OneOf::Left(true)),
+            workspace: Some(WorkspaceServerCapabilities {
+                workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                    supported: Some(true),
+                    change_notifications: Some(OneOf::Left(true)),
+                }),
+                file_operations: None,
+            }),
+            call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+            selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+            folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+            signature_help_provider: Some(SignatureHelpOptions {
+                trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
                 ..Default::default()
-            }
-        ));
-
-        capabilities.hover_provider = Some(HoverProviderCapability::Simple(true));
-        capabilities.definition_provider = Some(OneOf::Left(true));
-        capabilities.completion_provider = Some(CompletionOptions {
+            }),
+            rename_provider: Some(OneOf::Right(RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: Default::default(),
+            })),
+            completion_provider: Some(CompletionOptions {
+                ..Default::default()
+            }),
+            semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+                SemanticTokensOptions {
+                    legend: SemanticTokensLegend {
+                        token_types: LEGEND_TYPE.to_vec(),
+                        token_modifiers: LEGEND_MODIFIER.to_vec(),
+                    },
+                    full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                    ..Default::default()
+                }
+            )),
             ..Default::default()
-        });
+        };
 
         let initialize_result = InitializeResult {
             capabilities,
@@ -70,6 +131,10 @@ impl LanguageServer for Backend {
         Ok(initialize_result)
     }
 
+    async fn initialized(&self, _params: InitializedParams) {
+        self.index_workspace().await;
+    }
+
     async fn did_open(&self, params: DidOpenTextDocumentParams) -> () {
         self.client.log_message(MessageType::LOG, format!("Opened src file: {}", params.text_document.uri)).await;
         self.did_open_handler(params).await;
@@ -113,4 +178,102 @@ impl LanguageServer for Backend {
         self.client.log_message(MessageType::LOG, format!("Completion request at position: {:?}", params.text_document_position.position)).await;
         self.completion_handler(params).await
     }
-}
\ No newline at end of file
+
+    async fn semantic_tokens_full(&self, params: SemanticTokensParams) -> Result<Option<SemanticTokensResult>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Semantic tokens request for: {}", params.text_document.uri)).await;
+        self.semantic_tokens_full_handler(params).await
+    }
+
+    async fn semantic_tokens_full_delta(&self, params: SemanticTokensDeltaParams) -> Result<Option<SemanticTokensFullDeltaResult>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Semantic tokens delta request for: {}", params.text_document.uri)).await;
+        self.semantic_tokens_full_delta_handler(params).await
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>, tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("References request for: {}", params.text_document_position.text_document.uri)).await;
+        self.references_handler(params).await
+    }
+
+    async fn prepare_rename(&self, params: TextDocumentPositionParams) -> Result<Option<PrepareRenameResponse>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Prepare rename request for: {}", params.text_document.uri)).await;
+        self.prepare_rename_handler(params).await
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>, tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Rename request for: {}", params.text_document_position.text_document.uri)).await;
+        self.rename_handler(params).await
+    }
+
+    async fn document_highlight(&self, params: DocumentHighlightParams) -> Result<Option<Vec<DocumentHighlight>>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Document highlight request for: {}", params.text_document_position_params.text_document.uri)).await;
+        self.document_highlight_handler(params).await
+    }
+
+    async fn document_symbol(&self, params: DocumentSymbolParams) -> Result<Option<DocumentSymbolResponse>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Document symbol request for: {}", params.text_document.uri)).await;
+        self.document_symbol_handler(params).await
+    }
+
+    async fn symbol(&self, params: WorkspaceSymbolParams) -> Result<Option<Vec<SymbolInformation>>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Workspace symbol request for: {}", params.query)).await;
+        self.workspace_symbol_handler(params).await
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        self.client.log_message(MessageType::LOG, format!("Watched files changed: {} events", params.changes.len())).await;
+        self.did_change_watched_files_handler(params).await;
+    }
+
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        self.client.log_message(MessageType::LOG, format!("Workspace folders changed: {} added, {} removed",
+            params.event.added.len(), params.event.removed.len())).await;
+        self.did_change_workspace_folders_handler(params).await;
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        self.client.log_message(MessageType::LOG, "Configuration changed".to_string()).await;
+        self.did_change_configuration_handler(params).await;
+    }
+
+    async fn prepare_call_hierarchy(&self, params: CallHierarchyPrepareParams) -> Result<Option<Vec<CallHierarchyItem>>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Prepare call hierarchy request for: {}", params.text_document_position_params.text_document.uri)).await;
+        self.prepare_call_hierarchy_handler(params).await
+    }
+
+    async fn incoming_calls(&self, params: CallHierarchyIncomingCallsParams) -> Result<Option<Vec<CallHierarchyIncomingCall>>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Incoming calls request for: {}", params.item.name)).await;
+        self.incoming_calls_handler(params).await
+    }
+
+    async fn outgoing_calls(&self, params: CallHierarchyOutgoingCallsParams) -> Result<Option<Vec<CallHierarchyOutgoingCall>>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Outgoing calls request for: {}", params.item.name)).await;
+        self.outgoing_calls_handler(params).await
+    }
+
+    async fn selection_range(&self, params: SelectionRangeParams) -> Result<Option<Vec<SelectionRange>>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Selection range request for: {}", params.text_document.uri)).await;
+        self.selection_range_handler(params).await
+    }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Folding range request for: {}", params.text_document.uri)).await;
+        self.folding_range_handler(params).await
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>,
+        tower_lsp::jsonrpc::Error> {
+        self.client.log_message(MessageType::LOG, format!("Signature help request for: {}", params.text_document_position_params.text_document.uri)).await;
+        self.signature_help_handler(params).await
+    }
+}
@@ -0,0 +1,23 @@
+use tower_lsp::lsp_types::{DocumentHighlight, DocumentHighlightParams, Location, ReferenceParams};
+
+use super::Backend;
+
+impl Backend {
+    pub async fn references_handler(&self, params: ReferenceParams)
+    -> Result<Option<Vec<Location>>, tower_lsp::jsonrpc::Error> {
+        let uri = params.text_document_position.text_document.uri;
+        let doc_handler = self.documents.get(&uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let doc = doc_handler.lock().await;
+        let ranges = doc.find_references(params.text_document_position.position, params.context.include_declaration);
+        Ok(Some(ranges.into_iter().map(|range| Location { uri: uri.clone(), range }).collect()))
+    }
+
+    pub async fn document_highlight_handler(&self, params: DocumentHighlightParams)
+    -> Result<Option<Vec<DocumentHighlight>>, tower_lsp::jsonrpc::Error> {
+        let doc_handler = self.documents.get(&params.text_document_position_params.text_document.uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let doc = doc_handler.lock().await;
+        Ok(Some(doc.document_highlights(params.text_document_position_params.position)))
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{
+    PrepareRenameResponse, RenameParams, TextDocumentPositionParams, TextEdit, WorkspaceEdit,
+};
+
+use super::completion_reqs::KEYWORDS;
+use super::Backend;
+
+/// Whether `name` can be used as an identifier in SysY
+fn is_valid_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.contains(&name)
+}
+
+impl Backend {
+    pub async fn prepare_rename_handler(&self, params: TextDocumentPositionParams)
+    -> Result<Option<PrepareRenameResponse>, tower_lsp::jsonrpc::Error> {
+        let doc_handler = self.documents.get(&params.text_document.uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let doc = doc_handler.lock().await;
+        let range = doc.prepare_rename(params.position).map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+        Ok(Some(PrepareRenameResponse::Range(range)))
+    }
+
+    pub async fn rename_handler(&self, params: RenameParams)
+    -> Result<Option<WorkspaceEdit>, tower_lsp::jsonrpc::Error> {
+        if !is_valid_ident(&params.new_name) {
+            return Err(tower_lsp::jsonrpc::Error::invalid_params(
+                format!("'{}' is not a valid identifier", params.new_name)));
+        }
+        let uri = params.text_document_position.text_document.uri;
+        let doc_handler = self.documents.get(&uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let doc = doc_handler.lock().await;
+        let ranges = doc.rename(params.text_document_position.position, &params.new_name)
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+        let edits = ranges.into_iter()
+            .map(|range| TextEdit { range, new_text: params.new_name.clone() })
+            .collect();
+        Ok(Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri, edits)])),
+            ..Default::default()
+        }))
+    }
+}
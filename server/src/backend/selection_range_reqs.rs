@@ -0,0 +1,13 @@
+use tower_lsp::lsp_types::{SelectionRange, SelectionRangeParams};
+
+use super::Backend;
+
+impl Backend {
+    pub async fn selection_range_handler(&self, params: SelectionRangeParams)
+    -> Result<Option<Vec<SelectionRange>>, tower_lsp::jsonrpc::Error> {
+        let doc_handler = self.documents.get(&params.text_document.uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let doc = doc_handler.lock().await;
+        Ok(Some(doc.selection_ranges(&params.positions)))
+    }
+}
@@ -0,0 +1,23 @@
+use tower_lsp::lsp_types::{
+    SemanticTokensDeltaParams, SemanticTokensFullDeltaResult, SemanticTokensParams, SemanticTokensResult,
+};
+
+use super::Backend;
+
+impl Backend {
+    pub async fn semantic_tokens_full_handler(&self, params: SemanticTokensParams)
+    -> Result<Option<SemanticTokensResult>, tower_lsp::jsonrpc::Error> {
+        let doc_handler = self.documents.get(&params.text_document.uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let mut doc = doc_handler.lock().await;
+        Ok(Some(SemanticTokensResult::Tokens(doc.semantic_tokens_full())))
+    }
+
+    pub async fn semantic_tokens_full_delta_handler(&self, params: SemanticTokensDeltaParams)
+    -> Result<Option<SemanticTokensFullDeltaResult>, tower_lsp::jsonrpc::Error> {
+        let doc_handler = self.documents.get(&params.text_document.uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let mut doc = doc_handler.lock().await;
+        Ok(Some(doc.semantic_tokens_delta(&params.previous_result_id)))
+    }
+}
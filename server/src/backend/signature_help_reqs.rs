@@ -0,0 +1,13 @@
+use tower_lsp::lsp_types::{SignatureHelp, SignatureHelpParams};
+
+use super::Backend;
+
+impl Backend {
+    pub async fn signature_help_handler(&self, params: SignatureHelpParams)
+    -> Result<Option<SignatureHelp>, tower_lsp::jsonrpc::Error> {
+        let doc_handler = self.documents.get(&params.text_document_position_params.text_document.uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let doc = doc_handler.lock().await;
+        Ok(doc.signature_help(params.text_document_position_params.position))
+    }
+}
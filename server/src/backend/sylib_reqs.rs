@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use tower_lsp::lsp_types::{DidChangeConfigurationParams, InitializeParams, LSPAny, MessageType};
+
+use super::document_handler::load_runtime_library;
+use super::Backend;
+
+/// The `sylibPath` setting, which is empty when the built-in library is wanted
+fn sylib_path(settings: Option<&LSPAny>) -> Option<String> {
+    settings?.get("sylibPath")?.as_str()
+        .filter(|path| !path.trim().is_empty())
+        .map(|path| path.trim().to_string())
+}
+
+impl Backend {
+    /// Switch to the runtime library declared by the `sylib.h` at `path`, or
+    /// back to the built-in one, and re-check the open documents against it
+    async fn set_runtime_library(&self, path: Option<String>) {
+        let path = match path.map(PathBuf::from) {
+            Some(path) if path.is_relative() => {
+                let folders = self.workspace_folders.lock().await;
+                Some(folders.first().map_or(path.clone(), |folder| folder.join(&path)))
+            }
+            path => path,
+        };
+        match load_runtime_library(path.as_deref()) {
+            Ok(count) => {
+                let source = path.map_or("the built-in library".to_string(), |p| p.display().to_string());
+                self.client.log_message(MessageType::INFO, format!("Loaded {} runtime functions from {}", count, source)).await;
+            }
+            Err(err) => {
+                self.client.show_message(MessageType::WARNING, format!("Failed to load sylib.h: {}", err)).await;
+                return;
+            }
+        }
+
+        let open: Vec<_> = self.documents.iter().map(|entry| entry.key().clone()).collect();
+        for uri in open {
+            self.update_diagnostics(uri, None).await;
+        }
+    }
+
+    /// Load the `sylib.h` named by the `sylibPath` initialization option, if any
+    pub async fn init_runtime_library(&self, params: &InitializeParams) {
+        if let Some(path) = sylib_path(params.initialization_options.as_ref()) {
+            self.set_runtime_library(Some(path)).await;
+        }
+    }
+
+    pub async fn did_change_configuration_handler(&self, params: DidChangeConfigurationParams) {
+        let settings = params.settings.get("sysyAnalyzer").unwrap_or(&params.settings);
+        self.set_runtime_library(sylib_path(Some(settings))).await;
+    }
+}
@@ -0,0 +1,13 @@
+use tower_lsp::lsp_types::{DocumentSymbolParams, DocumentSymbolResponse};
+
+use super::Backend;
+
+impl Backend {
+    pub async fn document_symbol_handler(&self, params: DocumentSymbolParams)
+    -> Result<Option<DocumentSymbolResponse>, tower_lsp::jsonrpc::Error> {
+        let doc_handler = self.documents.get(&params.text_document.uri)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Document not found"))?;
+        let doc = doc_handler.lock().await;
+        Ok(Some(DocumentSymbolResponse::Nested(doc.document_symbols())))
+    }
+}
@@ -0,0 +1,220 @@
+use std::path::{Path, PathBuf};
+
+use tower_lsp::lsp_types::{
+    DidChangeWatchedFilesParams, DidChangeWorkspaceFoldersParams, FileChangeType, InitializeParams, MessageType,
+    SymbolInformation, Url, WorkspaceFolder, WorkspaceSymbolParams,
+};
+
+use super::document_handler::DocHandler;
+use super::Backend;
+
+/// Bound on the results of a workspace symbol query, which may otherwise
+/// list every function of a large testsuite
+const MAX_WORKSPACE_SYMBOLS: usize = 256;
+
+/// Every `.sy` file under `dir`, skipping hidden directories such as `.git`
+fn collect_sources(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if !entry.file_name().to_string_lossy().starts_with('.') {
+                collect_sources(&path, files);
+            }
+        } else if path.extension().is_some_and(|ext| ext == "sy") {
+            files.push(path);
+        }
+    }
+}
+
+/// Parse a file from disk and list its top-level symbols
+fn index_file(path: &Path, parser: &mut tree_sitter::Parser) -> Option<(Url, Vec<SymbolInformation>)> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let uri = Url::from_file_path(path).ok()?;
+    let symbols = DocHandler::new(&text, parser).workspace_symbols(&uri);
+    Some((uri, symbols))
+}
+
+/// Whether the file at `uri` lies under one of `folders`
+fn in_folders(uri: &Url, folders: &[PathBuf]) -> bool {
+    uri.to_file_path().is_ok_and(|path| folders.iter().any(|folder| path.starts_with(folder)))
+}
+
+fn new_parser() -> tree_sitter::Parser {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_sysy_parser::LANGUAGE.into()).expect("Error loading Sysy grammar");
+    parser
+}
+
+/// Score how well `name` matches `query` when the characters of the query
+/// appear in order in the name, ignoring case. Matches at the start of the
+/// name, after an underscore or right after the previous match score higher.
+fn fuzzy_score(query: &str, name: &str) -> Option<i64> {
+    let name: Vec<char> = name.chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last_match = None;
+    for q in query.chars() {
+        let offset = name[next..].iter().position(|c| c.eq_ignore_ascii_case(&q))?;
+        let idx = next + offset;
+        score += 1;
+        if idx == 0 {
+            score += 8;
+        } else if name[idx - 1] == '_' {
+            score += 4;
+        }
+        if last_match.is_some_and(|last| last + 1 == idx) {
+            score += 4;
+        }
+        if name[idx] == q {
+            score += 1;
+        }
+        last_match = Some(idx);
+        next = idx + 1;
+    }
+    // Prefer names that have little left over besides the query
+    Some(score * 16 - (name.len() - query.chars().count()) as i64)
+}
+
+impl Backend {
+    /// Remember the workspace folders to index once the client is ready
+    pub async fn set_workspace_folders(&self, params: &InitializeParams) {
+        #[allow(deprecated)]
+        let uris: Vec<Url> = match (&params.workspace_folders, &params.root_uri) {
+            (Some(folders), _) => folders.iter().map(|f| f.uri.clone()).collect(),
+            (None, Some(root)) => vec![root.clone()],
+            (None, None) => Vec::new(),
+        };
+        let mut folders = self.workspace_folders.lock().await;
+        *folders = uris.iter().filter_map(|uri| uri.to_file_path().ok()).collect();
+    }
+
+    /// Index every `.sy` file under the workspace folders in the background
+    pub async fn index_workspace(&self) {
+        let folders = self.workspace_folders.lock().await.clone();
+        self.index_folders(folders);
+    }
+
+    /// Index every `.sy` file under `folders` in the background
+    fn index_folders(&self, folders: Vec<PathBuf>) {
+        let index = self.workspace_index.clone();
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            let indexed = tokio::task::spawn_blocking(move || {
+                let mut files = Vec::new();
+                for folder in &folders {
+                    collect_sources(folder, &mut files);
+                }
+                let mut parser = new_parser();
+                for path in &files {
+                    if let Some((uri, symbols)) = index_file(path, &mut parser) {
+                        index.insert(uri, symbols);
+                    }
+                }
+                files.len()
+            }).await;
+            if let Ok(count) = indexed {
+                client.log_message(MessageType::INFO, format!("Indexed {} source files", count)).await;
+            }
+        });
+    }
+
+    /// Drop the files of removed folders from the index and index added ones
+    pub async fn did_change_workspace_folders_handler(&self, params: DidChangeWorkspaceFoldersParams) {
+        let to_paths = |folders: Vec<WorkspaceFolder>| -> Vec<PathBuf> {
+            folders.into_iter().filter_map(|f| f.uri.to_file_path().ok()).collect()
+        };
+        let removed = to_paths(params.event.removed);
+        let added = to_paths(params.event.added);
+        let mut folders = self.workspace_folders.lock().await;
+        folders.retain(|folder| !removed.contains(folder));
+        // A removed folder may be nested in a folder that is still open
+        let remaining = folders.clone();
+        self.workspace_index.retain(|uri, _| !in_folders(uri, &removed) || in_folders(uri, &remaining));
+        let added: Vec<_> = added.into_iter().filter(|folder| !folders.contains(folder)).collect();
+        folders.extend(added.iter().cloned());
+        drop(folders);
+        if !added.is_empty() {
+            self.index_folders(added);
+        }
+    }
+
+    pub async fn did_change_watched_files_handler(&self, params: DidChangeWatchedFilesParams) {
+        let mut parser = new_parser();
+        for change in params.changes {
+            if change.typ == FileChangeType::DELETED {
+                self.workspace_index.remove(&change.uri);
+                continue;
+            }
+            let indexed = change.uri.to_file_path().ok().and_then(|path| index_file(&path, &mut parser));
+            match indexed {
+                Some((uri, symbols)) => {
+                    self.workspace_index.insert(uri, symbols);
+                }
+                None => {
+                    self.workspace_index.remove(&change.uri);
+                }
+            }
+        }
+    }
+
+    pub async fn workspace_symbol_handler(&self, params: WorkspaceSymbolParams)
+    -> Result<Option<Vec<SymbolInformation>>, tower_lsp::jsonrpc::Error> {
+        // Open documents may have unsaved edits, so they take precedence over the index
+        let open: Vec<Url> = self.documents.iter().map(|entry| entry.key().clone()).collect();
+        let mut symbols: Vec<SymbolInformation> = self.workspace_index.iter()
+            .filter(|entry| !open.contains(entry.key()))
+            .flat_map(|entry| entry.value().clone())
+            .collect();
+        for uri in &open {
+            if let Some(doc_handler) = self.documents.get(uri) {
+                symbols.extend(doc_handler.lock().await.workspace_symbols(uri));
+            }
+        }
+
+        let mut scored: Vec<_> = symbols.into_iter()
+            .filter_map(|symbol| fuzzy_score(&params.query, &symbol.name).map(|score| (score, symbol)))
+            .collect();
+        scored.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score).then_with(|| a.name.cmp(&b.name)));
+        scored.truncate(MAX_WORKSPACE_SYMBOLS);
+        Ok(Some(scored.into_iter().map(|(_, symbol)| symbol).collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use tower_lsp::lsp_types::Url;
+
+    use super::{fuzzy_score, in_folders};
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("qsort", "quicksort").is_some());
+        assert!(fuzzy_score("QS", "quick_sort").is_some());
+        assert!(fuzzy_score("sortq", "quicksort").is_none());
+        assert!(fuzzy_score("", "main").is_some());
+        // Prefixes beat scattered matches, and shorter names beat longer ones
+        assert!(fuzzy_score("sort", "sort_array") > fuzzy_score("sort", "quicksort"));
+        assert!(fuzzy_score("qs", "quick_sort") > fuzzy_score("qs", "quicksort"));
+        assert!(fuzzy_score("main", "main") > fuzzy_score("main", "main_loop"));
+    }
+
+    #[test]
+    fn test_in_folders() {
+        let folders = vec![PathBuf::from("/work/a"), PathBuf::from("/work/b/tests")];
+        let file = |path| Url::parse(path).unwrap();
+        assert!(in_folders(&file("file:///work/a/main.sy"), &folders));
+        assert!(in_folders(&file("file:///work/b/tests/sort/qsort.sy"), &folders));
+        assert!(!in_folders(&file("file:///work/b/main.sy"), &folders));
+        // Components are compared whole, not as string prefixes
+        assert!(!in_folders(&file("file:///work/ab/main.sy"), &folders));
+        assert!(!in_folders(&file("untitled:Untitled-1"), &folders));
+    }
+}
@@ -1,5 +1,5 @@
 use tower_lsp::{LspService, Server};
-use clap::{command, Parser};
+use clap::Parser;
 use std::net::SocketAddr;
 mod backend;
 #[derive(Parser, Debug)]
@@ -33,6 +33,6 @@ async fn main() {
     }
 
 
-    let (service, socket) = LspService::new(|client| backend::Backend::new(client));
+    let (service, socket) = LspService::new(backend::Backend::new);
     Server::new(stdin, stdout, socket).serve(service).await;
 }
@@ -20,15 +20,15 @@ module.exports = grammar({
   ],
 
   precedences: _ => [
-    // Precedence from lowest to highest
+    // Precedence from highest to lowest
     [
-      'logical_or',
-      'logical_and',
-      'equality',
-      'relational',
-      'additive',
-      'multiplicative',
       'unary',
+      'multiplicative',
+      'additive',
+      'relational',
+      'equality',
+      'logical_and',
+      'logical_or',
     ],
   ],
 
@@ -39,6 +39,7 @@ module.exports = grammar({
     
     Type: $ => choice(
       "int",
+      "float",
       "void"),
 
     FuncDef: $ => seq(
@@ -81,8 +82,8 @@ module.exports = grammar({
     ConstDecl: $ => seq(
       "const",
       field("type", $.Type),
-      field("defs", $.ConstDef,
-      repeat(seq(",", field("defs", $.ConstDef)))),
+      field("defs", $.ConstDef),
+      repeat(seq(",", field("defs", $.ConstDef))),
       ";"
     ),
 
@@ -136,7 +137,10 @@ module.exports = grammar({
       )
     ),
 
-    Lval: $ => seq($.Ident, repeat(seq("[", $.Exp, "]"))),
+    Lval: $ => seq(
+      field("ident", $.Ident),
+      repeat(seq("[", field("index", $.Exp), "]"))
+    ),
 
     PrimaryExp: $ => choice(
       $.Number,
@@ -145,16 +149,35 @@ module.exports = grammar({
     ),
 
     _Stmt: $ => choice(
-      seq($.Lval, "=", $.Exp, ";"),
-      seq(optional($.Exp), ";"),
+      $.AssignStmt,
+      $.ExpStmt,
       $.Block,
-      prec.right(seq("if", "(", $.Exp, ")", $._Stmt, optional(seq("else", $._Stmt)))),
-      seq("while", "(", $.Exp, ")", $._Stmt),
-      seq("break", ";"),
-      seq("continue", ";"),
-      seq("return", optional($.Exp), ";")
+      $.IfStmt,
+      $.WhileStmt,
+      $.BreakStmt,
+      $.ContinueStmt,
+      $.ReturnStmt
     ),
 
+    AssignStmt: $ => seq(field("target", $.Lval), "=", field("value", $.Exp), ";"),
+
+    // Also the empty statement `;`
+    ExpStmt: $ => seq(optional(field("value", $.Exp)), ";"),
+
+    IfStmt: $ => prec.right(seq(
+      "if", "(", field("condition", $.Exp), ")",
+      field("consequence", $._Stmt),
+      optional(seq("else", field("alternative", $._Stmt)))
+    )),
+
+    WhileStmt: $ => seq("while", "(", field("condition", $.Exp), ")", field("body", $._Stmt)),
+
+    BreakStmt: $ => seq("break", ";"),
+
+    ContinueStmt: $ => seq("continue", ";"),
+
+    ReturnStmt: $ => seq("return", optional(field("value", $.Exp)), ";"),
+
     _BlockItem: $ => choice(
       $._Decl,
       $._Stmt
@@ -172,7 +195,7 @@ module.exports = grammar({
     ),
 
     FuncCall: $ => seq(
-      $.Ident,
+      field("callee", $.Ident),
       "(",
       optional($.FuncRParams),
       ")"
@@ -181,33 +204,45 @@ module.exports = grammar({
     Exp: $ => choice(
       $.PrimaryExp,
       $.FuncCall,
-      prec.right('unary', seq("+", $.Exp)),
-      prec.right('unary', seq("-", $.Exp)),
-      prec.right('unary', seq("!", $.Exp)),
-      prec.left('additive', seq($.Exp, "+", $.Exp)),
-      prec.left('additive', seq($.Exp, "-", $.Exp)),
-      prec.left('multiplicative', seq($.Exp, "*", $.Exp)),
-      prec.left('multiplicative', seq($.Exp, "/", $.Exp)),
-      prec.left('multiplicative', seq($.Exp, "%", $.Exp)),
-      prec.left('relational', seq($.Exp, "<", $.Exp)),
-      prec.left('relational', seq($.Exp, ">", $.Exp)),
-      prec.left('relational', seq($.Exp, "<=", $.Exp)),
-      prec.left('relational', seq($.Exp, ">=", $.Exp)),
-      prec.left('equality', seq($.Exp, "==", $.Exp)),
-      prec.left('equality', seq($.Exp, "!=", $.Exp)),
-      prec.left('logical_and', seq($.Exp, "&&", $.Exp)),
-      prec.left('logical_or', seq($.Exp, "||", $.Exp)),
+      $.UnaryExp,
+      $.BinaryExp
+    ),
+
+    UnaryExp: $ => prec.right('unary', seq(
+      field("operator", choice("+", "-", "!")),
+      field("operand", $.Exp)
+    )),
+
+    BinaryExp: $ => choice(
+      ...[
+        ['multiplicative', choice("*", "/", "%")],
+        ['additive', choice("+", "-")],
+        ['relational', choice("<", ">", "<=", ">=")],
+        ['equality', choice("==", "!=")],
+        ['logical_and', "&&"],
+        ['logical_or', "||"],
+      ].map(([precedence, operator]) => prec.left(precedence, seq(
+        field("left", $.Exp),
+        field("operator", operator),
+        field("right", $.Exp)
+      )))
     ),
 
     Number: $ => choice(
       $.Decimal,
       $.Octal,
-      $.Hexadecimal
+      $.Hexadecimal,
+      $.Float
     ),
     Ident: $ => /[a-zA-Z_][a-zA-Z0-9_]*/,
     Decimal: $ => /[1-9][0-9]*/,
     Octal: $ => /0[0-7]*/,
     Hexadecimal: $ => /0[xX][0-9a-fA-F]+/,
+    // Decimal floats need a point or an exponent, hexadecimal ones always an exponent
+    Float: $ => token(choice(
+      /([0-9]+\.[0-9]*|\.[0-9]+)([eE][+-]?[0-9]+)?/,
+      /[0-9]+[eE][+-]?[0-9]+/,
+      /0[xX]([0-9a-fA-F]+\.?[0-9a-fA-F]*|\.[0-9a-fA-F]+)[pP][+-]?[0-9]+/)),
 
     comment: _ => token(choice(
           /\/\*[^*]*\*+([^/*][^*]*\*+)*\//, 